@@ -5,7 +5,7 @@ use drawille::{PixelColor, PixelColor::TrueColor, Turtle};
 fn main() {
     // Rainbow using true colors 
     let mut turtle_true = Turtle::new(0., 0.);
-    let colors1 = vec![
+    let colors1 = [
         TrueColor{r: 255, g: 0,   b: 0},
         TrueColor{r: 255, g: 127, b:0},
         TrueColor{r: 255, g: 255, b: 0},
@@ -30,7 +30,7 @@ fn main() {
 
     // Rainbow using adaptive colors
     let mut turtle_buildin= Turtle::new(0., 0.);
-    let colors2 = vec![
+    let colors2 = [
        PixelColor::Red,
        PixelColor::Yellow,
        PixelColor::Green,
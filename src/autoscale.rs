@@ -0,0 +1,128 @@
+//! Fitting data onto the terminal.
+//!
+//! A `Mapper` turns positions in the space of some data into pixels, scaled and centred so
+//! that a chosen box of the data fits a canvas. `Canvas::fit_points` and `Canvas::fit_bounds`
+//! go a step further and create a canvas the size of the terminal to go with it.
+
+use std::env;
+
+use super::Canvas;
+
+/// Converts between positions in data space and pixels on a `Canvas`.
+///
+/// Both directions are scaled alike, so shapes in the data keep their proportions. Larger y
+/// values in the data are drawn higher up, as on a graph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mapper {
+    min: (f64, f64),
+    max: (f64, f64),
+    scale: f64,
+    origin: (f64, f64),
+}
+
+impl Mapper {
+    /// Creates a `Mapper` that fits the box of data from `min` to `max` into the `width` by
+    /// `height` pixels of a canvas whose top-left corner is at `(left, top)`.
+    pub fn new(
+        min: (f64, f64),
+        max: (f64, f64),
+        (left, top): (u32, u32),
+        (width, height): (u32, u32),
+    ) -> Mapper {
+        let (dx, dy) = ((max.0 - min.0).abs(), (max.1 - min.1).abs());
+        let (w, h) = (
+            width.saturating_sub(1) as f64,
+            height.saturating_sub(1) as f64,
+        );
+        let scale = match (dx > 0.0, dy > 0.0) {
+            (true, true) => f64::min(w / dx, h / dy),
+            (true, false) => w / dx,
+            (false, true) => h / dy,
+            (false, false) => 1.0,
+        };
+        // Whatever room is left over is shared between both sides.
+        let origin = (
+            left as f64 + (w - dx * scale) / 2.0,
+            top as f64 + (h - dy * scale) / 2.0,
+        );
+        Mapper {
+            min: (min.0.min(max.0), min.1.min(max.1)),
+            max: (min.0.max(max.0), min.1.max(max.1)),
+            scale,
+            origin,
+        }
+    }
+
+    /// Returns the pixel that the point `(x, y)` of the data falls on.
+    ///
+    /// Points outside the box given to the `Mapper` may land on negative coordinates, and are
+    /// moved to the nearest edge of the canvas.
+    pub fn map(&self, x: f64, y: f64) -> (u32, u32) {
+        let px = self.origin.0 + (x - self.min.0) * self.scale;
+        let py = self.origin.1 + (self.max.1 - y) * self.scale;
+        (px.round().max(0.0) as u32, py.round().max(0.0) as u32)
+    }
+
+    /// Returns the point of the data that the pixel `(x, y)` stands for, such as for working
+    /// out what the mouse is pointing at.
+    pub fn unmap(&self, x: u32, y: u32) -> (f64, f64) {
+        (
+            self.min.0 + (x as f64 - self.origin.0) / self.scale,
+            self.max.1 - (y as f64 - self.origin.1) / self.scale,
+        )
+    }
+
+    /// Returns the number of pixels that a distance of `1.0` in the data takes up.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+}
+
+impl Canvas {
+    /// Creates a `Canvas` that fills the terminal, along with a `Mapper` that fits all of
+    /// `points` onto it with `margin` characters of room along each edge.
+    ///
+    /// The terminal size is found with `terminal_size`, or taken to be 80 by 24 characters if
+    /// it can’t be found. Programs that want to ask the terminal itself can make a `Canvas` and
+    /// `Mapper` of their own size with `Canvas::new` and `Mapper::new`.
+    pub fn fit_points(points: &[(f64, f64)], margin: u32) -> (Canvas, Mapper) {
+        let mut min = (f64::INFINITY, f64::INFINITY);
+        let mut max = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in points {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        if points.is_empty() {
+            min = (0.0, 0.0);
+            max = (0.0, 0.0);
+        }
+        Canvas::fit_bounds(min, max, margin)
+    }
+
+    /// Creates a `Canvas` that fills the terminal, along with a `Mapper` that fits the box of
+    /// data from `min` to `max` onto it, like `fit_points`.
+    pub fn fit_bounds(min: (f64, f64), max: (f64, f64), margin: u32) -> (Canvas, Mapper) {
+        let (cols, rows) = terminal_size().unwrap_or((80, 24));
+        // A `Canvas` draws one more row and column of characters than its dimensions, which
+        // are left for that.
+        let (width, height) = (cols.saturating_sub(1) * 2, rows.saturating_sub(1) * 4);
+        let (mx, my) = (margin * 2, margin * 4);
+        let room = (width.saturating_sub(mx * 2), height.saturating_sub(my * 2));
+        let mapper = Mapper::new(min, max, (mx, my), room);
+        (Canvas::new(width, height), mapper)
+    }
+}
+
+/// Works out the size of the terminal in characters, as `(columns, rows)`, or `None` if it
+/// can’t be found.
+///
+/// The size is read from the `COLUMNS` and `LINES` environment variables, and is only found if
+/// both are set. The terminal itself is never asked, so this is cheap and has no side effects;
+/// many shells don’t export these variables, though.
+pub fn terminal_size() -> Option<(u32, u32)> {
+    let var = |name| env::var(name).ok().and_then(|v| v.trim().parse().ok());
+    match (var("COLUMNS"), var("LINES")) {
+        (Some(cols), Some(rows)) => Some((cols, rows)),
+        _ => None,
+    }
+}
@@ -0,0 +1,423 @@
+//! A command-line tool for drawing images, plots and turtle scripts with Braille characters.
+//!
+//! Built with the `cli` feature: `cargo install drawille --features cli`.
+
+extern crate drawille;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+use drawille::chart::LineChart;
+use drawille::{terminal_size, Canvas, Palette, PixelCanvas, PixelColor, Turtle};
+
+const USAGE: &str = "\
+Usage:
+    drawille image <file> [--threshold <0-255>] [--edges]
+    drawille plot [<file>]
+    drawille turtle [<file>]
+
+Commands:
+    image     Draws a PBM, PGM or PPM image, setting the pixels darker than the threshold
+              (128 by default), or the outlines in the image with --edges.
+    plot      Plots numbers from a file or standard input as a line chart. Each line holds
+              values separated by commas or whitespace: one column is plotted against the
+              line number, and with more, the first column is x and each other is a series.
+              Lines that aren’t numbers, such as CSV headers, are skipped.
+    turtle    Runs a turtle script from a file or standard input, such as
+              `F100 R90 F100`; see `Turtle::run` for the commands.
+
+Options:
+    --size <cols>x<rows>    The size to draw at, in characters, instead of the terminal’s.
+";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        // Writing fails when the output is piped into something that stops reading early,
+        // such as `head`, which is no reason to complain.
+        Ok(frame) => {
+            let _ = writeln!(io::stdout(), "{}", frame);
+        }
+        Err(err) => {
+            eprintln!("drawille: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let mut files = Vec::new();
+    let mut threshold = 128;
+    let mut edges = false;
+    let mut size = None;
+    let mut args = args.iter();
+    let command = match args.next() {
+        Some(command) if command != "-h" && command != "--help" => command,
+        _ => return Ok(USAGE.trim_end().to_owned()),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for `{}`", arg));
+        match arg.as_str() {
+            "--threshold" => {
+                let value = value()?;
+                threshold = value
+                    .parse()
+                    .map_err(|_| format!("invalid threshold `{}`", value))?;
+            }
+            "--edges" => edges = true,
+            "--size" => {
+                let value = value()?;
+                size = Some(parse_size(value).ok_or(format!("invalid size `{}`", value))?);
+            }
+            _ => files.push(arg.as_str()),
+        }
+    }
+    if files.len() > 1 {
+        return Err(format!("unexpected argument `{}`", files[1]));
+    }
+    // A `Canvas` draws one more row and column of characters than its dimensions, which are
+    // left for that.
+    let (cols, rows) = size
+        .or_else(terminal_size)
+        .or_else(stty_size)
+        .unwrap_or((80, 24));
+    let (width, height) = (cols.saturating_sub(1) * 2, rows.saturating_sub(1) * 4);
+
+    let canvas = match command.as_str() {
+        "image" => {
+            let file = files.first().ok_or("no image given")?;
+            let data = fs::read(file).map_err(|e| format!("{}: {}", file, e))?;
+            let (w, h, luma) = decode_netpbm(&data).map_err(|e| format!("{}: {}", file, e))?;
+            let canvas = if edges {
+                Canvas::from_luma_edges(w, h, &luma, threshold)
+            } else {
+                Canvas::from_luma(w, h, &luma, threshold)
+            };
+            canvas.fit_to(width, height, 0.5)
+        }
+        "plot" => plot(&read_input(files.first())?, width, height)?,
+        "turtle" => turtle(&read_input(files.first())?, width, height)?,
+        _ => {
+            return Err(format!(
+                "unknown command `{}`\n\n{}",
+                command,
+                USAGE.trim_end()
+            ))
+        }
+    };
+    Ok(canvas.frame())
+}
+
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let mut parts = size.splitn(2, 'x');
+    let cols = parts.next()?.parse().ok()?;
+    let rows = parts.next()?.parse().ok()?;
+    Some((cols, rows))
+}
+
+/// Asks the terminal that standard input is connected to for its size with `stty`, for when
+/// `COLUMNS` and `LINES` aren’t set.
+fn stty_size() -> Option<(u32, u32)> {
+    if !cfg!(unix) {
+        return None;
+    }
+    let output = process::Command::new("stty")
+        .arg("size")
+        .stdin(process::Stdio::inherit())
+        .stderr(process::Stdio::null())
+        .output()
+        .ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    let mut sizes = text.split_whitespace().map(|n| n.parse::<u32>().ok());
+    match (sizes.next()??, sizes.next()??) {
+        (0, _) | (_, 0) => None,
+        (rows, cols) => Some((cols, rows)),
+    }
+}
+
+/// Reads the whole of `file`, or of standard input if there is none.
+fn read_input(file: Option<&&str>) -> Result<String, String> {
+    match file {
+        Some(file) => fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e)),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("standard input: {}", e))?;
+            Ok(input)
+        }
+    }
+}
+
+fn plot(input: &str, width: u32, height: u32) -> Result<Canvas, String> {
+    let mut series: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut count = 0;
+    for line in input.lines() {
+        let values: Result<Vec<f64>, _> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse::<f64>())
+            .collect();
+        let values = match values {
+            Ok(ref values) if !values.is_empty() => values,
+            _ => continue,
+        };
+        let (x, ys) = match values.len() {
+            1 => (count as f64, &values[..]),
+            _ => (values[0], &values[1..]),
+        };
+        count += 1;
+        for (i, &y) in ys.iter().enumerate() {
+            if series.len() <= i {
+                series.push(Vec::new());
+            }
+            series[i].push((x, y));
+        }
+    }
+    if series.is_empty() {
+        return Err("no numbers to plot".to_owned());
+    }
+    let mut chart = LineChart::new();
+    if series.len() > 1 {
+        chart = chart.palette(Palette::default());
+    }
+    for points in &series {
+        chart = chart.series(points);
+    }
+    Ok(chart.render(width, height))
+}
+
+/// Where the turtle starts, far enough from the edges of the canvas that it can walk some way
+/// up and to the left.
+const START: f32 = 20000.0;
+
+fn turtle(script: &str, width: u32, height: u32) -> Result<Canvas, String> {
+    // The script is run once to find how far the drawing reaches, so that it can be moved
+    // onto the canvas the second time.
+    let mut bounds = Turtle::with_canvas(START, START, Bounds::default());
+    bounds.run(script).map_err(|e| e.to_string())?;
+    let (min_x, min_y) = match bounds.cvs.min {
+        Some(min) => min,
+        None => return Ok(Canvas::new(width, height)),
+    };
+    let mut turtle = Turtle::new(START - min_x as f32, START - min_y as f32);
+    turtle.run(script).map_err(|e| e.to_string())?;
+    Ok(turtle.cvs.fit_to(width, height, 0.0))
+}
+
+/// A `PixelCanvas` that only keeps the smallest coordinates of the pixels set on it.
+#[derive(Default)]
+struct Bounds {
+    min: Option<(u32, u32)>,
+}
+
+impl PixelCanvas for Bounds {
+    fn set(&mut self, x: u32, y: u32) {
+        self.min = Some(match self.min {
+            Some((min_x, min_y)) => (min_x.min(x), min_y.min(y)),
+            None => (x, y),
+        });
+    }
+
+    fn set_colored(&mut self, x: u32, y: u32, _: PixelColor) {
+        self.set(x, y);
+    }
+
+    fn unset(&mut self, _: u32, _: u32) {}
+
+    fn frame(&self) -> String {
+        String::new()
+    }
+}
+
+/// Decodes a black and white (PBM), greyscale (PGM) or colour (PPM) Netpbm image, in its
+/// binary or plain text form, into its width, height and luma values.
+fn decode_netpbm(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let invalid = || "not a valid PBM, PGM or PPM image".to_owned();
+    let (channels, binary, bitmap) = match data.get(..2) {
+        Some(b"P1") => (1, false, true),
+        Some(b"P4") => (1, true, true),
+        Some(b"P2") => (1, false, false),
+        Some(b"P5") => (1, true, false),
+        Some(b"P3") => (3, false, false),
+        Some(b"P6") => (3, true, false),
+        _ => return Err(invalid()),
+    };
+
+    // The header is three numbers separated by whitespace and comments, followed by a single
+    // whitespace character before binary pixel data.
+    let mut pos = 2;
+    let mut number = |data: &[u8]| -> Option<u32> {
+        loop {
+            match data.get(pos)? {
+                b'#' => {
+                    while *data.get(pos)? != b'\n' {
+                        pos += 1;
+                    }
+                }
+                c if c.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+        let start = pos;
+        while data.get(pos).is_some_and(|c| c.is_ascii_digit()) {
+            pos += 1;
+        }
+        std::str::from_utf8(&data[start..pos]).ok()?.parse().ok()
+    };
+    let width = number(data).ok_or_else(invalid)?;
+    let height = number(data).ok_or_else(invalid)?;
+    // Bitmaps have no maximum value, and use 1 for black; they are read as if 1 was white, in
+    // a greyscale image whose maximum value is 1.
+    let maxval = if bitmap {
+        1
+    } else {
+        number(data)
+            .filter(|&m| m > 0 && m < 65536)
+            .ok_or_else(invalid)?
+    };
+    let count = width as usize * height as usize * channels;
+
+    let samples: Vec<u32> = if bitmap && binary {
+        // Each row is packed into bytes, most significant bit first.
+        let pixels = data.get(pos + 1..).ok_or_else(invalid)?;
+        let row_bytes = (width as usize).div_ceil(8);
+        let rows = pixels.chunks_exact(row_bytes.max(1)).take(height as usize);
+        rows.flat_map(|row| (0..width as usize).map(move |x| row[x / 8] >> (7 - x % 8) & 1))
+            .map(|bit| 1 - bit as u32)
+            .collect()
+    } else if bitmap {
+        // Pixels are single digits, which needn’t be separated by whitespace.
+        let digits = data[pos..].iter().filter(|c| !c.is_ascii_whitespace());
+        let bits = digits.take(count).map(|&c| match c {
+            b'0' => Ok(1),
+            b'1' => Ok(0),
+            _ => Err(invalid()),
+        });
+        bits.collect::<Result<_, _>>()?
+    } else if binary {
+        let pixels = data.get(pos + 1..).ok_or_else(invalid)?;
+        if maxval < 256 {
+            pixels.iter().take(count).map(|&b| b as u32).collect()
+        } else {
+            let pairs = pixels.chunks_exact(2).take(count);
+            pairs.map(|p| (p[0] as u32) << 8 | p[1] as u32).collect()
+        }
+    } else {
+        let text = std::str::from_utf8(&data[pos..]).map_err(|_| invalid())?;
+        let values = text
+            .split_whitespace()
+            .take(count)
+            .map(|v| v.parse::<u32>());
+        values.collect::<Result<_, _>>().map_err(|_| invalid())?
+    };
+    if samples.len() < count {
+        return Err("image data ends early".to_owned());
+    }
+
+    let luma = samples
+        .chunks(channels)
+        .map(|p| {
+            let value = match *p {
+                [r, g, b] => (r * 299 + g * 587 + b * 114) / 1000,
+                _ => p[0],
+            };
+            (value.min(maxval) * 255 / maxval) as u8
+        })
+        .collect();
+    Ok((width, height, luma))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_netpbm, turtle};
+    use drawille::{Canvas, Turtle};
+
+    fn decode(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+        decode_netpbm(data)
+    }
+
+    #[test]
+    fn bitmaps() {
+        let plain = decode(b"P1\n# a comment\n3 2\n1 0 1\n010\n").unwrap();
+        assert_eq!(plain, (3, 2, vec![0, 255, 0, 255, 0, 255]));
+        // Rows are padded to a whole byte.
+        let binary = decode(b"P4\n3 2\n\xa0\x40").unwrap();
+        assert_eq!(binary, plain);
+        let wide = decode(b"P4 9 1 \xff\x00").unwrap();
+        assert_eq!(wide, (9, 1, vec![0, 0, 0, 0, 0, 0, 0, 0, 255]));
+        assert!(decode(b"P1 2 2 1 0 2 1").is_err());
+    }
+
+    #[test]
+    fn greyscale() {
+        let plain = decode(b"P2 # width\n2 # height\n2\n# maxval\n15\n0 15\n5 20\n").unwrap();
+        assert_eq!(plain, (2, 2, vec![0, 255, 85, 255]));
+        let binary = decode(b"P5\n2 2\n15\n\x00\x0f\x05\x14").unwrap();
+        assert_eq!(binary, plain);
+    }
+
+    #[test]
+    fn colour() {
+        let plain = decode(b"P3\n2 1\n255\n255 0 0  0 0 255\n").unwrap();
+        assert_eq!(plain, (2, 1, vec![76, 29]));
+        let binary = decode(b"P6\n2 1\n255\n\xff\x00\x00\x00\x00\xff").unwrap();
+        assert_eq!(binary, plain);
+    }
+
+    #[test]
+    fn sixteen_bit_samples() {
+        let plain = decode(b"P2 3 1 65535 0 32768 65535").unwrap();
+        assert_eq!(plain, (3, 1, vec![0, 127, 255]));
+        let binary = decode(b"P5 3 1 65535 \x00\x00\x80\x00\xff\xff").unwrap();
+        assert_eq!(binary, plain);
+    }
+
+    #[test]
+    fn truncated_and_invalid() {
+        let early = Err("image data ends early".to_owned());
+        assert_eq!(decode(b"P2 2 2 255 1 2 3"), early);
+        assert_eq!(decode(b"P5 2 2 255 \x01\x02\x03"), early);
+        assert_eq!(decode(b"P5 2 1 65535 \x01\x02\x03"), early);
+        assert_eq!(decode(b"P6 1 1 255 \x01\x02"), early);
+        assert_eq!(decode(b"P4 9 2 \xff\x00\xff"), early);
+        assert_eq!(decode(b"P1 2 2 101"), early);
+        for data in &[
+            &b"P7 1 1 255 0"[..],
+            b"P2 1 1",
+            b"P2 1 1 0 0",
+            b"P2 1 1 70000 0",
+            b"P3 1 1 255 1 x 3",
+            b"",
+        ] {
+            assert!(decode(data).is_err(), "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn turtle_moves_drawing_onto_canvas() {
+        // The drawing goes up and to the left of where the turtle starts.
+        let canvas = turtle("L90 F30 L90 F20", 200, 200).unwrap();
+        let mut expected = Turtle::new(20.0, 30.0);
+        expected.run("L90 F30 L90 F20").unwrap();
+        assert!(canvas.eq_pixels(&expected.cvs));
+        assert!(canvas.get(0, 0) && canvas.get(20, 30) && canvas.get(20, 0));
+
+        assert!(turtle("U F10", 20, 20)
+            .unwrap()
+            .eq_pixels(&Canvas::new(0, 0)));
+        assert!(turtle("F10 X", 20, 20).is_err());
+    }
+
+    #[test]
+    fn turtle_fits_large_drawings() {
+        let canvas = turtle("F1000 R90 F400", 100, 40).unwrap();
+        let frame = canvas.frame();
+        assert!(frame.lines().count() <= 11, "{}", frame);
+        assert!(frame.lines().all(|l| l.chars().count() <= 51), "{}", frame);
+        // Both the line across the top and the one down the right are still there.
+        assert!(canvas.get(0, 0) && canvas.get(99, 0) && canvas.get(99, 39));
+    }
+}
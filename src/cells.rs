@@ -0,0 +1,226 @@
+//! Storage for the cells of a canvas.
+//!
+//! Cells are kept in a hash map, alongside an index of which square chunks of cells are in use.
+//! The index keeps rendering and sizing cheap for huge, sparse canvases: the extent of a canvas
+//! is found from its chunks rather than by visiting every cell, and empty chunks can be skipped
+//! over without looking up each cell in them.
+
+use std::collections::hash_map;
+use std::mem;
+use std::ops::Index;
+
+use fnv::FnvHashMap;
+
+use super::PixelColor;
+
+/// The number of cells along each side of a chunk, which can be at most 16 so that each row of
+/// a chunk fits in a `u16` bitmask.
+pub const CHUNK: u16 = 16;
+
+const _: () = assert!(CHUNK >= 1 && CHUNK as u32 <= u16::BITS);
+
+/// The contents of a cell: the Braille dots set in it, the letter in it (when no dots are set),
+/// whether it is coloured and its colour.
+pub type Cell = (u8, char, bool, PixelColor);
+
+/// The cells of a canvas, indexed by `(column, row)`.
+#[derive(Clone, Debug, Default)]
+pub struct Cells {
+    map: FnvHashMap<(u16, u16), Cell>,
+    /// For each chunk in use, a bitmask of the cells in use in each of its rows.
+    chunks: FnvHashMap<(u16, u16), [u16; CHUNK as usize]>,
+}
+
+impl Cells {
+    pub fn get(&self, pos: &(u16, u16)) -> Option<&Cell> {
+        self.map.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &(u16, u16)) -> Option<&mut Cell> {
+        self.map.get_mut(pos)
+    }
+
+    pub fn keys(&self) -> hash_map::Keys<'_, (u16, u16), Cell> {
+        self.map.keys()
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, (u16, u16), Cell> {
+        self.map.iter()
+    }
+
+    pub fn insert(&mut self, pos: (u16, u16), cell: Cell) {
+        if self.map.insert(pos, cell).is_none() {
+            self.mark(pos, true);
+        }
+    }
+
+    /// Returns the cell at `pos`, putting `cell` there first if there isn’t one yet.
+    pub fn entry(&mut self, pos: (u16, u16), cell: Cell) -> &mut Cell {
+        if !self.map.contains_key(&pos) {
+            self.mark(pos, true);
+        }
+        self.map.entry(pos).or_insert(cell)
+    }
+
+    pub fn remove(&mut self, pos: &(u16, u16)) -> Option<Cell> {
+        let cell = self.map.remove(pos);
+        if cell.is_some() {
+            self.mark(*pos, false);
+        }
+        cell
+    }
+
+    pub fn retain<F: FnMut(&(u16, u16), &mut Cell) -> bool>(&mut self, mut keep: F) {
+        let mut removed = Vec::new();
+        self.map.retain(|pos, cell| {
+            let kept = keep(pos, cell);
+            if !kept {
+                removed.push(*pos);
+            }
+            kept
+        });
+        for pos in removed {
+            self.mark(pos, false);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.chunks.clear();
+    }
+
+    /// Returns the largest column and the largest row used by any cell, or `None` if there are
+    /// no cells.
+    pub fn extent(&self) -> Option<(u16, u16)> {
+        let mut extent = None;
+        for (&(cx, cy), rows) in &self.chunks {
+            let cols = rows.iter().fold(0u16, |acc, &mask| acc | mask);
+            // The highest bit set, counted from bit 0 of the mask whatever size chunks are.
+            let col = cx * CHUNK + (u16::BITS - 1 - cols.leading_zeros()) as u16;
+            let row = cy * CHUNK + rows.iter().rposition(|&mask| mask != 0).unwrap_or(0) as u16;
+            extent = Some(match extent {
+                Some((maxcol, maxrow)) => (col.max(maxcol), row.max(maxrow)),
+                None => (col, row),
+            });
+        }
+        extent
+    }
+
+    /// Returns an estimate of the number of bytes of memory taken up by the cells and their
+    /// index.
+    pub fn memory_footprint(&self) -> usize {
+        // Hash maps need a byte of bookkeeping for each entry they have room for.
+        let cell = mem::size_of::<((u16, u16), Cell)>() + 1;
+        let chunk = mem::size_of::<((u16, u16), [u16; CHUNK as usize])>() + 1;
+        mem::size_of::<Cells>() + self.map.capacity() * cell + self.chunks.capacity() * chunk
+    }
+
+    /// Returns whether any cell of the chunk holding the cell at `pos` is in use.
+    pub fn chunk_used(&self, pos: (u16, u16)) -> bool {
+        self.chunks.contains_key(&(pos.0 / CHUNK, pos.1 / CHUNK))
+    }
+
+    fn mark(&mut self, (col, row): (u16, u16), used: bool) {
+        let key = (col / CHUNK, row / CHUNK);
+        let bit = 1 << (col % CHUNK);
+        if used {
+            self.chunks.entry(key).or_insert([0; CHUNK as usize])[(row % CHUNK) as usize] |= bit;
+        } else if let Some(rows) = self.chunks.get_mut(&key) {
+            rows[(row % CHUNK) as usize] &= !bit;
+            if rows.iter().all(|&mask| mask == 0) {
+                self.chunks.remove(&key);
+            }
+        }
+    }
+}
+
+impl PartialEq for Cells {
+    fn eq(&self, other: &Cells) -> bool {
+        self.map == other.map
+    }
+}
+
+impl Index<&(u16, u16)> for Cells {
+    type Output = Cell;
+
+    fn index(&self, pos: &(u16, u16)) -> &Cell {
+        &self.map[pos]
+    }
+}
+
+impl<'a> IntoIterator for &'a Cells {
+    type Item = (&'a (u16, u16), &'a Cell);
+    type IntoIter = hash_map::Iter<'a, (u16, u16), Cell>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter()
+    }
+}
+
+impl Extend<((u16, u16), Cell)> for Cells {
+    fn extend<I: IntoIterator<Item = ((u16, u16), Cell)>>(&mut self, cells: I) {
+        for (pos, cell) in cells {
+            self.insert(pos, cell);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cells, CHUNK};
+    use PixelColor;
+
+    const CELL: (u8, char, bool, PixelColor) = (1, ' ', false, PixelColor::White);
+
+    #[test]
+    fn index_follows_cells() {
+        let mut cells = Cells::default();
+        assert_eq!(cells.extent(), None);
+        cells.insert((3, CHUNK * 2 + 7), CELL);
+        cells.insert((CHUNK * 2 + 5, 1), CELL);
+        assert_eq!(cells.extent(), Some((CHUNK * 2 + 5, CHUNK * 2 + 7)));
+        assert!(cells.chunk_used((0, CHUNK * 2)));
+        assert!(cells.chunk_used((CHUNK * 2, 0)));
+        assert!(!cells.chunk_used((0, 0)));
+
+        assert_eq!(cells.remove(&(CHUNK * 2 + 5, 1)), Some(CELL));
+        assert_eq!(cells.remove(&(CHUNK * 2 + 5, 1)), None);
+        assert!(!cells.chunk_used((CHUNK * 2, 0)));
+        assert_eq!(cells.extent(), Some((3, CHUNK * 2 + 7)));
+    }
+
+    #[test]
+    fn chunks_stay_used_while_any_cell_is() {
+        let mut cells = Cells::default();
+        cells.insert((1, 1), CELL);
+        cells.insert((2, 1), CELL);
+        cells.entry((15, 15), CELL).0 = 2;
+        assert_eq!(cells[&(15, 15)].0, 2);
+        cells.remove(&(1, 1));
+        assert!(cells.chunk_used((0, 0)));
+        assert_eq!(cells.extent(), Some((15, 15)));
+
+        cells.retain(|&(col, _), _| col != 15);
+        assert_eq!(cells.extent(), Some((2, 1)));
+        cells.retain(|_, _| false);
+        assert!(!cells.chunk_used((0, 0)));
+        assert_eq!(cells.extent(), None);
+    }
+
+    #[test]
+    fn clear_and_equality() {
+        let mut a = Cells::default();
+        let mut b = Cells::default();
+        a.extend(vec![((0, 0), CELL), ((100, 200), CELL)]);
+        b.insert((100, 200), CELL);
+        b.insert((0, 0), CELL);
+        assert_eq!(a, b);
+        b.get_mut(&(0, 0)).unwrap().0 = 3;
+        assert!(a != b);
+
+        a.clear();
+        assert_eq!(a, Cells::default());
+        assert_eq!(a.extent(), None);
+        assert!(!a.chunk_used((100, 200)));
+    }
+}
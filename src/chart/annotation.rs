@@ -0,0 +1,97 @@
+use std::ops::RangeInclusive;
+
+use super::super::{Canvas, PixelColor};
+use super::Frame;
+
+/// A mark pinned to the data of a chart, such as a threshold or a note about a point.
+///
+/// Positions are in the units of the data, and annotations outside the range of the axes
+/// aren’t drawn, apart from the parts of bands that overlap it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Annotation {
+    /// A dashed line across the plot at a y value, such as a limit or a target.
+    HorizontalLine(f64),
+    /// A dashed line up the plot at an x value, such as to mark when something happened.
+    VerticalLine(f64),
+    /// A shaded band across the plot between two y values.
+    HorizontalBand(f64, f64),
+    /// A shaded band up the plot between two x values.
+    VerticalBand(f64, f64),
+    /// Text with its top-left corner at the point `(x, y)`. Text is never coloured.
+    Text(f64, f64, String),
+}
+
+/// Draws the lines and bands among `annotations` if `text` is false, or the text if it is
+/// true, so that text can be drawn over the data and the rest under it.
+pub fn draw(
+    canvas: &mut Canvas,
+    frame: Frame,
+    (xbounds, ybounds): ((f64, f64), (f64, f64)),
+    annotations: &[(Annotation, Option<PixelColor>)],
+    text: bool,
+) {
+    let within = |v: f64, (min, max): (f64, f64)| v >= min && v <= max;
+    let span = |a: f64, b: f64, (min, max): (f64, f64)| {
+        let (lo, hi) = (a.min(b).max(min), a.max(b).min(max));
+        if lo <= hi {
+            Some((lo, hi))
+        } else {
+            None
+        }
+    };
+    for &(ref annotation, color) in annotations {
+        let mut set = |canvas: &mut Canvas, x: u32, y: u32| match color {
+            Some(color) => canvas.set_colored(x, y, color),
+            None => canvas.set(x, y),
+        };
+        match *annotation {
+            Annotation::Text(x, y, ref label)
+                if text && within(x, xbounds) && within(y, ybounds) =>
+            {
+                let (x, y) = (frame.x(x, xbounds), frame.y(y, ybounds));
+                canvas.text(x, y, u32::MAX, label);
+            }
+            _ if text => {}
+            Annotation::HorizontalLine(y) if within(y, ybounds) => {
+                let y = frame.y(y, ybounds);
+                for x in (frame.left..=frame.right).filter(|x| x % 4 < 2) {
+                    set(canvas, x, y);
+                }
+            }
+            Annotation::VerticalLine(x) if within(x, xbounds) => {
+                let x = frame.x(x, xbounds);
+                for y in (frame.top..=frame.bottom).filter(|y| y % 4 < 2) {
+                    set(canvas, x, y);
+                }
+            }
+            Annotation::HorizontalBand(a, b) => {
+                if let Some((lo, hi)) = span(a, b, ybounds) {
+                    let rows = frame.y(hi, ybounds)..=frame.y(lo, ybounds);
+                    shade(canvas, (frame.left..=frame.right, rows), &mut set);
+                }
+            }
+            Annotation::VerticalBand(a, b) => {
+                if let Some((lo, hi)) = span(a, b, xbounds) {
+                    let cols = frame.x(lo, xbounds)..=frame.x(hi, xbounds);
+                    shade(canvas, (cols, frame.top..=frame.bottom), &mut set);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sets every other pixel of every other row of the given region, for a light shading.
+fn shade<F>(
+    canvas: &mut Canvas,
+    (cols, rows): (RangeInclusive<u32>, RangeInclusive<u32>),
+    set: &mut F,
+) where
+    F: FnMut(&mut Canvas, u32, u32),
+{
+    for y in rows.filter(|y| y % 2 == 0) {
+        for x in cols.clone().filter(|x| x % 2 == 0) {
+            set(canvas, x, y);
+        }
+    }
+}
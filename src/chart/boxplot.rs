@@ -0,0 +1,158 @@
+use std::cmp;
+
+use super::super::{Canvas, PixelColor};
+use super::{draw_axes, draw_centred, y_labels, Axis, Frame};
+
+/// The summary statistics drawn for one box of a `BoxPlot`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoxStats {
+    /// The smallest sample within 1.5 interquartile ranges of the lower quartile.
+    pub low: f64,
+    /// The lower quartile.
+    pub q1: f64,
+    /// The median.
+    pub median: f64,
+    /// The upper quartile.
+    pub q3: f64,
+    /// The largest sample within 1.5 interquartile ranges of the upper quartile.
+    pub high: f64,
+    /// The samples lying outside the whiskers.
+    pub outliers: Vec<f64>,
+}
+
+impl BoxStats {
+    /// Computes the statistics for a set of samples, ignoring any that are NaN or infinite.
+    ///
+    /// Quartiles are found by linear interpolation between the closest ranks. Returns `None` if
+    /// there are no samples.
+    pub fn from_samples(samples: &[f64]) -> Option<BoxStats> {
+        let mut sorted: Vec<f64> = samples.iter().cloned().filter(|v| v.is_finite()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let quantile = |p: f64| {
+            let h = (sorted.len() - 1) as f64 * p;
+            let (lo, hi) = (h.floor() as usize, h.ceil() as usize);
+            sorted[lo] + (h - lo as f64) * (sorted[hi] - sorted[lo])
+        };
+        let (q1, median, q3) = (quantile(0.25), quantile(0.5), quantile(0.75));
+        let fence = 1.5 * (q3 - q1);
+        let inside = |v: &&f64| **v >= q1 - fence && **v <= q3 + fence;
+        let low = *sorted.iter().find(inside).unwrap_or(&sorted[0]);
+        let high = *sorted
+            .iter()
+            .rev()
+            .find(inside)
+            .unwrap_or(&sorted[sorted.len() - 1]);
+        let outliers = sorted
+            .iter()
+            .cloned()
+            .filter(|&v| v < low || v > high)
+            .collect();
+
+        Some(BoxStats {
+            low,
+            q1,
+            median,
+            q3,
+            high,
+            outliers,
+        })
+    }
+}
+
+/// A box-and-whisker plot, with one box per category.
+#[derive(Clone, Debug, Default)]
+pub struct BoxPlot {
+    boxes: Vec<(String, Option<BoxStats>)>,
+    y_axis: Axis,
+    color: Option<PixelColor>,
+}
+
+impl BoxPlot {
+    /// Creates a new, empty `BoxPlot`.
+    pub fn new() -> BoxPlot {
+        BoxPlot::default()
+    }
+
+    /// Adds a box summarising `samples` under the category `label`, and returns the `BoxPlot`
+    /// for use again.
+    pub fn add(mut self, label: &str, samples: &[f64]) -> BoxPlot {
+        self.boxes
+            .push((label.to_owned(), BoxStats::from_samples(samples)));
+        self
+    }
+
+    /// Sets the y axis of the `BoxPlot`, and returns it for use again.
+    pub fn y_axis(mut self, axis: Axis) -> BoxPlot {
+        self.y_axis = axis;
+        self
+    }
+
+    /// Sets the colour of the boxes, and returns the `BoxPlot` for use again.
+    pub fn color(mut self, color: PixelColor) -> BoxPlot {
+        self.color = Some(color);
+        self
+    }
+
+    /// Returns the statistics of each box, in the order they were added.
+    pub fn stats(&self) -> Vec<(&str, Option<&BoxStats>)> {
+        self.boxes
+            .iter()
+            .map(|(label, stats)| (&label[..], stats.as_ref()))
+            .collect()
+    }
+
+    /// Draws the `BoxPlot` onto a new `Canvas` of `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Canvas {
+        let stats = self.boxes.iter().filter_map(|(_, s)| s.as_ref());
+        let (min, max) = stats.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), s| {
+            let lo = s.outliers.iter().cloned().fold(s.low, f64::min);
+            let hi = s.outliers.iter().cloned().fold(s.high, f64::max);
+            (min.min(lo), max.max(hi))
+        });
+        let bounds = self.y_axis.bounds(min, max);
+        let (labels, left) = y_labels(&self.y_axis, bounds);
+        let frame = Frame::new(width, height, left, 4);
+
+        let mut canvas = Canvas::new(width, height);
+        draw_axes(&mut canvas, frame, &labels, bounds);
+        if self.boxes.is_empty() {
+            return canvas;
+        }
+
+        let slot = (frame.right - frame.left) / self.boxes.len() as u32;
+        let half = cmp::max(slot / 4, 1);
+        for (i, (label, stats)) in self.boxes.iter().enumerate() {
+            let cx = frame.left + slot * i as u32 + slot / 2;
+            draw_centred(&mut canvas, cx, frame.bottom + 4, label);
+            let s = match *stats {
+                Some(ref s) => s,
+                None => continue,
+            };
+
+            let y = |v: f64| frame.y(v, bounds);
+            let (left, right) = (cx.saturating_sub(half), cx + half);
+            let mut line = |x1, y1, x2, y2| match self.color {
+                Some(color) => canvas.line_colored(x1, y1, x2, y2, color),
+                None => canvas.line(x1, y1, x2, y2),
+            };
+
+            line(cx, y(s.high), cx, y(s.q3));
+            line(cx, y(s.q1), cx, y(s.low));
+            line(cx - half / 2, y(s.high), cx + half / 2, y(s.high));
+            line(cx - half / 2, y(s.low), cx + half / 2, y(s.low));
+            line(left, y(s.q3), right, y(s.q3));
+            line(left, y(s.q1), right, y(s.q1));
+            line(left, y(s.q3), left, y(s.q1));
+            line(right, y(s.q3), right, y(s.q1));
+            line(left, y(s.median), right, y(s.median));
+            for &v in &s.outliers {
+                line(cx, y(v), cx, y(v));
+            }
+        }
+        canvas
+    }
+}
@@ -0,0 +1,98 @@
+use super::super::{Canvas, PixelColor};
+use super::{draw_axes, y_labels, Axis, Frame};
+
+/// A candlestick chart of open, high, low and close prices.
+///
+/// Each candle has a wick spanning its low to its high, and a body spanning its open to its
+/// close, coloured according to whether the price went up or down.
+#[derive(Clone, Debug)]
+pub struct Candlestick {
+    candles: Vec<(f64, f64, f64, f64)>,
+    y_axis: Axis,
+    up: PixelColor,
+    down: PixelColor,
+}
+
+impl Default for Candlestick {
+    fn default() -> Candlestick {
+        Candlestick {
+            candles: Vec::new(),
+            y_axis: Axis::default(),
+            up: PixelColor::Green,
+            down: PixelColor::Red,
+        }
+    }
+}
+
+impl Candlestick {
+    /// Creates a new, empty `Candlestick` chart, with green up candles and red down ones.
+    pub fn new() -> Candlestick {
+        Candlestick::default()
+    }
+
+    /// Adds a candle, and returns the `Candlestick` chart for use again.
+    pub fn add(mut self, open: f64, high: f64, low: f64, close: f64) -> Candlestick {
+        self.candles.push((open, high, low, close));
+        self
+    }
+
+    /// Adds a candle for each `(open, high, low, close)` tuple, and returns the `Candlestick`
+    /// chart for use again.
+    pub fn candles(mut self, candles: &[(f64, f64, f64, f64)]) -> Candlestick {
+        self.candles.extend_from_slice(candles);
+        self
+    }
+
+    /// Sets the y axis of the chart, and returns it for use again.
+    pub fn y_axis(mut self, axis: Axis) -> Candlestick {
+        self.y_axis = axis;
+        self
+    }
+
+    /// Sets the colour of candles that close at or above their open, and returns the chart for
+    /// use again.
+    pub fn up_color(mut self, color: PixelColor) -> Candlestick {
+        self.up = color;
+        self
+    }
+
+    /// Sets the colour of candles that close below their open, and returns the chart for use
+    /// again.
+    pub fn down_color(mut self, color: PixelColor) -> Candlestick {
+        self.down = color;
+        self
+    }
+
+    /// Draws the chart onto a new `Canvas` of `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Canvas {
+        let (min, max) = self
+            .candles
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), c| {
+                (min.min(c.2), max.max(c.1))
+            });
+        let bounds = self.y_axis.bounds(min, max);
+        let (labels, left) = y_labels(&self.y_axis, bounds);
+        let frame = Frame::new(width, height, left, 0);
+
+        let mut canvas = Canvas::new(width, height);
+        draw_axes(&mut canvas, frame, &labels, bounds);
+        if self.candles.is_empty() {
+            return canvas;
+        }
+
+        let slot = (frame.right - frame.left - 1) as f64 / self.candles.len() as f64;
+        let half = (slot * 0.3) as u32;
+        for (i, &(open, high, low, close)) in self.candles.iter().enumerate() {
+            let cx = frame.left + 1 + (slot * (i as f64 + 0.5)) as u32;
+            let color = if close >= open { self.up } else { self.down };
+            let y = |v: f64| frame.y(v, bounds);
+
+            canvas.line_colored(cx, y(high), cx, y(low), color);
+            for x in cx.saturating_sub(half)..=cx + half {
+                canvas.line_colored(x, y(open), x, y(close), color);
+            }
+        }
+        canvas
+    }
+}
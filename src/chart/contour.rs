@@ -0,0 +1,196 @@
+use super::super::{Canvas, PixelColor};
+use super::{draw_axes, draw_x_labels, y_labels, Axis, Frame};
+
+/// A contour plot of a two-dimensional field of values, drawing lines where the field crosses
+/// each requested level.
+///
+/// The field is a grid of rows, with the first row at the bottom of the plot and the first
+/// value of each row on the left. By default the axes are numbered by the positions in the
+/// grid; giving them a range spreads the grid across it instead.
+#[derive(Clone, Debug, Default)]
+pub struct Contour {
+    field: Vec<Vec<f64>>,
+    levels: Vec<(f64, Option<PixelColor>)>,
+    x_axis: Axis,
+    y_axis: Axis,
+}
+
+impl Contour {
+    /// Creates a new `Contour` plot with no field and no levels.
+    pub fn new() -> Contour {
+        Contour::default()
+    }
+
+    /// Sets the field of values to plot, as a slice of rows, and returns the `Contour` for use
+    /// again.
+    ///
+    /// Rows shorter than the first are treated as if they were cut off at the right.
+    pub fn field(mut self, rows: &[Vec<f64>]) -> Contour {
+        self.field = rows.to_vec();
+        self
+    }
+
+    /// Adds a level to draw a line at, and returns the `Contour` for use again.
+    pub fn level(mut self, value: f64) -> Contour {
+        self.levels.push((value, None));
+        self
+    }
+
+    /// Adds a level to draw a line at in the given colour, and returns the `Contour` for use
+    /// again.
+    pub fn level_colored(mut self, value: f64, color: PixelColor) -> Contour {
+        self.levels.push((value, Some(color)));
+        self
+    }
+
+    /// Adds several levels to draw lines at, and returns the `Contour` for use again.
+    pub fn levels(mut self, values: &[f64]) -> Contour {
+        self.levels.extend(values.iter().map(|&v| (v, None)));
+        self
+    }
+
+    /// Sets the x axis of the `Contour` plot, and returns it for use again.
+    pub fn x_axis(mut self, axis: Axis) -> Contour {
+        self.x_axis = axis;
+        self
+    }
+
+    /// Sets the y axis of the `Contour` plot, and returns it for use again.
+    pub fn y_axis(mut self, axis: Axis) -> Contour {
+        self.y_axis = axis;
+        self
+    }
+
+    /// Draws the `Contour` plot onto a new `Canvas` of `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Canvas {
+        let rows = self.field.len();
+        let cols = self.field.iter().map(|r| r.len()).min().unwrap_or(0);
+        let xbounds = self.x_axis.exact_bounds(0.0, cols.saturating_sub(1) as f64);
+        let ybounds = self.y_axis.exact_bounds(0.0, rows.saturating_sub(1) as f64);
+        let (labels, left) = y_labels(&self.y_axis, ybounds);
+        let frame = Frame::new(width, height, left, 4);
+
+        let mut canvas = Canvas::new(width, height);
+        draw_axes(&mut canvas, frame, &labels, ybounds);
+        draw_x_labels(&mut canvas, frame, &self.x_axis.labels(xbounds), xbounds);
+        if rows < 2 || cols < 2 {
+            return canvas;
+        }
+
+        // Converts a position in the grid into pixels.
+        let pixel = |(i, j): (f64, f64)| {
+            let x = xbounds.0 + (xbounds.1 - xbounds.0) * i / (cols - 1) as f64;
+            let y = ybounds.0 + (ybounds.1 - ybounds.0) * j / (rows - 1) as f64;
+            (frame.x(x, xbounds), frame.y(y, ybounds))
+        };
+        for &(level, color) in &self.levels {
+            for j in 0..rows - 1 {
+                for i in 0..cols - 1 {
+                    for (from, to) in self.segments(i, j, level) {
+                        let ((x1, y1), (x2, y2)) = (pixel(from), pixel(to));
+                        match color {
+                            Some(color) => canvas.line_colored(x1, y1, x2, y2, color),
+                            None => canvas.line(x1, y1, x2, y2),
+                        }
+                    }
+                }
+            }
+        }
+        canvas
+    }
+
+    /// Returns the pieces of the line at `level` crossing the square of the grid whose
+    /// bottom-left corner is at `(i, j)`, using marching squares.
+    fn segments(&self, i: usize, j: usize, level: f64) -> Vec<((f64, f64), (f64, f64))> {
+        let (fi, fj) = (i as f64, j as f64);
+        // The corners, anticlockwise from the bottom left.
+        let corners = [
+            ((fi, fj), self.field[j][i]),
+            ((fi + 1.0, fj), self.field[j][i + 1]),
+            ((fi + 1.0, fj + 1.0), self.field[j + 1][i + 1]),
+            ((fi, fj + 1.0), self.field[j + 1][i]),
+        ];
+        // Where the line crosses each edge, starting with the bottom one and going round.
+        let mut crossings = [None; 4];
+        for (k, crossing) in crossings.iter_mut().enumerate() {
+            let ((x1, y1), v1) = corners[k];
+            let ((x2, y2), v2) = corners[(k + 1) % 4];
+            if (v1 < level) != (v2 < level) {
+                let t = (level - v1) / (v2 - v1);
+                *crossing = Some((x1 + (x2 - x1) * t, y1 + (y2 - y1) * t));
+            }
+        }
+        match crossings {
+            [Some(bottom), Some(right), Some(top), Some(left)] => {
+                // A saddle: the value in the middle decides which corners are joined up.
+                let centre = corners.iter().map(|c| c.1).sum::<f64>() / 4.0;
+                if (centre < level) == (corners[0].1 < level) {
+                    vec![(bottom, right), (top, left)]
+                } else {
+                    vec![(left, bottom), (right, top)]
+                }
+            }
+            _ => {
+                let points: Vec<_> = crossings.iter().filter_map(|&c| c).collect();
+                match points[..] {
+                    [from, to] => vec![(from, to)],
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Contour;
+
+    fn square(rows: &[Vec<f64>], level: f64) -> Vec<((f64, f64), (f64, f64))> {
+        Contour::new().field(rows).segments(0, 0, level)
+    }
+
+    #[test]
+    fn no_crossing() {
+        let rows = [vec![0.0, 0.0], vec![0.0, 0.0]];
+        assert_eq!(square(&rows, 0.5), []);
+        assert_eq!(square(&rows, -0.5), []);
+    }
+
+    #[test]
+    fn cuts_off_a_corner() {
+        let rows = [vec![0.0, 0.0], vec![0.0, 1.0]];
+        assert_eq!(square(&rows, 0.5), [((1.0, 0.5), (0.5, 1.0))]);
+        let rows = [vec![1.0, 1.0], vec![1.0, 0.0]];
+        assert_eq!(square(&rows, 0.5), [((1.0, 0.5), (0.5, 1.0))]);
+    }
+
+    #[test]
+    fn crosses_the_square() {
+        let rows = [vec![0.0, 0.0], vec![1.0, 1.0]];
+        assert_eq!(square(&rows, 0.25), [((1.0, 0.25), (0.0, 0.25))]);
+    }
+
+    #[test]
+    fn saddles_follow_the_centre() {
+        let rows = [vec![1.0, 0.0], vec![0.0, 1.0]];
+        // The centre is 0.5, on the same side of 0.25 as the bottom-left corner.
+        assert_eq!(
+            square(&rows, 0.25),
+            [((0.75, 0.0), (1.0, 0.25)), ((0.25, 1.0), (0.0, 0.75))]
+        );
+        assert_eq!(
+            square(&rows, 0.75),
+            [((0.0, 0.25), (0.25, 0.0)), ((1.0, 0.75), (0.75, 1.0))]
+        );
+    }
+
+    #[test]
+    fn render_draws_only_crossed_levels() {
+        let rows = vec![vec![0.0, 1.0, 2.0]; 3];
+        let empty = Contour::new().field(&rows).render(40, 20).frame();
+        let outside = Contour::new().field(&rows).level(5.0).render(40, 20);
+        assert_eq!(outside.frame(), empty);
+        let inside = Contour::new().field(&rows).level(1.5).render(40, 20);
+        assert!(inside.frame() != empty);
+    }
+}
@@ -0,0 +1,319 @@
+use super::super::{Canvas, Palette, PixelColor};
+use super::{
+    annotation, draw_axes, draw_line, draw_right_axis, draw_x_labels, y_labels, Annotation, Axis,
+    Frame, Series,
+};
+
+/// The uncertainty of a point plotted by a `LineChart`, drawn as whiskers with caps at their
+/// ends.
+///
+/// Each field is how far the error reaches from the point in that direction, in the units of
+/// the data, and no whisker is drawn in directions where it is zero. Asymmetric errors can be
+/// given with a struct literal, such as
+/// `ErrorBar { below: 0.5, above: 2.0, ..ErrorBar::default() }`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ErrorBar {
+    pub left: f64,
+    pub right: f64,
+    pub below: f64,
+    pub above: f64,
+}
+
+impl ErrorBar {
+    /// Creates an `ErrorBar` reaching `error` above and below the point.
+    pub fn vertical(error: f64) -> ErrorBar {
+        ErrorBar::symmetric(0.0, error)
+    }
+
+    /// Creates an `ErrorBar` reaching `error` to the left and right of the point.
+    pub fn horizontal(error: f64) -> ErrorBar {
+        ErrorBar::symmetric(error, 0.0)
+    }
+
+    /// Creates an `ErrorBar` reaching `x` to either side of the point and `y` above and below
+    /// it.
+    pub fn symmetric(x: f64, y: f64) -> ErrorBar {
+        ErrorBar {
+            left: x,
+            right: x,
+            below: y,
+            above: y,
+        }
+    }
+}
+
+/// A chart plotting series of `(x, y)` points joined by lines.
+#[derive(Clone, Debug, Default)]
+pub struct LineChart {
+    series: Vec<Series>,
+    x_axis: Axis,
+    y_axis: Axis,
+    secondary_axis: Axis,
+    annotations: Vec<(Annotation, Option<PixelColor>)>,
+    palette: Option<Palette>,
+}
+
+impl LineChart {
+    /// Creates a new, empty `LineChart`.
+    pub fn new() -> LineChart {
+        LineChart::default()
+    }
+
+    /// Adds a series of `(x, y)` points, and returns the `LineChart` for use again.
+    pub fn series(mut self, points: &[(f64, f64)]) -> LineChart {
+        self.series.push(Series::new(points, None));
+        self
+    }
+
+    /// Adds a series of `(x, y)` points drawn in the given colour, and returns the `LineChart`
+    /// for use again.
+    pub fn series_colored(mut self, points: &[(f64, f64)], color: PixelColor) -> LineChart {
+        self.series.push(Series::new(points, Some(color)));
+        self
+    }
+
+    /// Gives the points of the series added last the error bars in `errors`, in order, and
+    /// returns the `LineChart` for use again.
+    ///
+    /// Points past the end of `errors` are left without error bars. The range of each axis
+    /// fitted to the data is widened to hold the whiskers.
+    pub fn errors(mut self, errors: &[ErrorBar]) -> LineChart {
+        if let Some(series) = self.series.last_mut() {
+            series.errors = errors.to_vec();
+        }
+        self
+    }
+
+    /// Plots the series added last against the secondary y axis on the right of the chart, and
+    /// returns the `LineChart` for use again.
+    ///
+    /// The secondary axis has its own range and labels, fitted to the series plotted against
+    /// it, so two quantities in different units can be shown together.
+    pub fn secondary(mut self) -> LineChart {
+        if let Some(series) = self.series.last_mut() {
+            series.secondary = true;
+        }
+        self
+    }
+
+    /// Sets a palette to colour the series without a colour of their own from, in order, and
+    /// returns the `LineChart` for use again.
+    pub fn palette(mut self, palette: Palette) -> LineChart {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Adds an annotation, such as a threshold line, and returns the `LineChart` for use again.
+    ///
+    /// Lines and bands are drawn under the data, in the order they were added, and text is
+    /// drawn over it.
+    pub fn annotate(mut self, annotation: Annotation) -> LineChart {
+        self.annotations.push((annotation, None));
+        self
+    }
+
+    /// Adds an annotation drawn in the given colour, and returns the `LineChart` for use again.
+    pub fn annotate_colored(mut self, annotation: Annotation, color: PixelColor) -> LineChart {
+        self.annotations.push((annotation, Some(color)));
+        self
+    }
+
+    /// Sets the x axis of the `LineChart`, and returns it for use again.
+    pub fn x_axis(mut self, axis: Axis) -> LineChart {
+        self.x_axis = axis;
+        self
+    }
+
+    /// Sets the y axis of the `LineChart`, and returns it for use again.
+    pub fn y_axis(mut self, axis: Axis) -> LineChart {
+        self.y_axis = axis;
+        self
+    }
+
+    /// Sets the secondary y axis of the `LineChart`, on the right, and returns it for use
+    /// again.
+    ///
+    /// The axis is only drawn if a series is plotted against it with `secondary`.
+    pub fn secondary_axis(mut self, axis: Axis) -> LineChart {
+        self.secondary_axis = axis;
+        self
+    }
+
+    /// Draws the `LineChart` onto a new `Canvas` of `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Canvas {
+        let (xmin, xmax, _, _) = self.extent(None);
+        let xbounds = self.x_axis.bounds(xmin, xmax);
+        let (_, _, ymin, ymax) = self.extent(Some(false));
+        let ybounds = self.y_axis.bounds(ymin, ymax);
+        let (labels, left) = y_labels(&self.y_axis, ybounds);
+        let mut frame = Frame::new(width, height, left, 4);
+        let secondary = self.series.iter().any(|s| s.secondary);
+        let (_, _, ymin, ymax) = self.extent(Some(true));
+        let y2bounds = self.secondary_axis.bounds(ymin, ymax);
+        let (right_labels, right) = y_labels(&self.secondary_axis, y2bounds);
+        if secondary {
+            frame.right = frame.right.saturating_sub(right).max(frame.left + 1);
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        draw_axes(&mut canvas, frame, &labels, ybounds);
+        draw_x_labels(&mut canvas, frame, &self.x_axis.labels(xbounds), xbounds);
+        let bounds = (xbounds, ybounds);
+        if secondary {
+            draw_right_axis(&mut canvas, frame, &right_labels, y2bounds);
+        }
+        annotation::draw(&mut canvas, frame, bounds, &self.annotations, false);
+
+        for (i, series) in self.series.iter().enumerate() {
+            let color = series.color(self.palette.as_ref(), i);
+            let ybounds = if series.secondary { y2bounds } else { ybounds };
+            let pixels: Vec<(u32, u32)> = series
+                .points
+                .iter()
+                .map(|&(x, y)| (frame.x(x, xbounds), frame.y(y, ybounds)))
+                .collect();
+            for pair in pixels.windows(2) {
+                draw_line(
+                    &mut canvas,
+                    color,
+                    pair[0].0,
+                    pair[0].1,
+                    pair[1].0,
+                    pair[1].1,
+                );
+            }
+            if let [(x, y)] = pixels[..] {
+                draw_line(&mut canvas, color, x, y, x, y);
+            }
+            for (&(x, y), e) in series.points.iter().zip(&series.errors) {
+                let (px, py) = (frame.x(x, xbounds), frame.y(y, ybounds));
+                if e.below != 0.0 || e.above != 0.0 {
+                    let (top, bottom) =
+                        (frame.y(y + e.above, ybounds), frame.y(y - e.below, ybounds));
+                    draw_line(&mut canvas, color, px, top, px, bottom);
+                    for &end in &[top, bottom] {
+                        draw_line(&mut canvas, color, px.saturating_sub(1), end, px + 1, end);
+                    }
+                }
+                if e.left != 0.0 || e.right != 0.0 {
+                    let (left, right) =
+                        (frame.x(x - e.left, xbounds), frame.x(x + e.right, xbounds));
+                    draw_line(&mut canvas, color, left, py, right, py);
+                    for &end in &[left, right] {
+                        draw_line(&mut canvas, color, end, py.saturating_sub(1), end, py + 1);
+                    }
+                }
+            }
+        }
+        annotation::draw(&mut canvas, frame, bounds, &self.annotations, true);
+        canvas
+    }
+
+    /// Returns the smallest and largest x and y values of the series, widened to hold their
+    /// error bars, as `(xmin, xmax, ymin, ymax)`. Only the series plotted against the
+    /// secondary axis or only the others are counted if `secondary` is given.
+    fn extent(&self, secondary: Option<bool>) -> (f64, f64, f64, f64) {
+        let series = self
+            .series
+            .iter()
+            .filter(|s| secondary.is_none() || secondary == Some(s.secondary));
+        let points = series.flat_map(|s| {
+            s.points.iter().enumerate().map(move |(i, &(x, y))| {
+                let e = s.errors.get(i).cloned().unwrap_or_default();
+                (x - e.left, x + e.right, y - e.below, y + e.above)
+            })
+        });
+        points.fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(xmin, xmax, ymin, ymax), (x1, x2, y1, y2)| {
+                (xmin.min(x1), xmax.max(x2), ymin.min(y1), ymax.max(y2))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorBar, LineChart};
+    use chart::Axis;
+
+    fn rows(chart: &LineChart, width: u32, height: u32) -> Vec<Vec<char>> {
+        let canvas = chart.render(width, height);
+        canvas
+            .rows()
+            .iter()
+            .map(|r| r.trim_end().chars().collect())
+            .collect()
+    }
+
+    #[test]
+    fn errors_widen_the_extent() {
+        let chart = LineChart::new()
+            .series(&[(0.0, 5.0), (10.0, 5.0), (20.0, 5.0)])
+            .errors(&[ErrorBar::symmetric(1.0, 10.0), ErrorBar::vertical(2.0)]);
+        assert_eq!(chart.extent(None), (-1.0, 20.0, -5.0, 15.0));
+        let chart = chart.errors(&[ErrorBar {
+            right: 30.0,
+            below: 1.0,
+            ..ErrorBar::default()
+        }]);
+        assert_eq!(chart.extent(None), (0.0, 30.0, 4.0, 5.0));
+    }
+
+    #[test]
+    fn error_bars_have_caps() {
+        let axis = || Axis::new().range(0.0, 10.0).formatter(|_| String::new());
+        let chart = LineChart::new()
+            .series(&[(5.0, 5.0)])
+            .errors(&[ErrorBar::symmetric(2.0, 4.0)])
+            .x_axis(axis())
+            .y_axis(axis());
+        let canvas = chart.render(43, 45);
+        // The plot runs from x = 2 to 42 and y = 0 to 40, four pixels to each unit.
+        let (x, y) = (2 + 20, 20);
+        assert!((4..=36).all(|y| canvas.get(x, y)));
+        assert!(!canvas.get(x, 3) && !canvas.get(x, 37));
+        assert!((14..=30).all(|x| canvas.get(x, y)));
+        assert!(!canvas.get(13, y) && !canvas.get(31, y));
+        for &end in &[4, 36] {
+            assert!(canvas.get(x - 1, end) && canvas.get(x + 1, end));
+            assert!(!canvas.get(x - 1, end + 1) && !canvas.get(x - 2, end));
+        }
+        for &end in &[14, 30] {
+            assert!(canvas.get(end, y - 1) && canvas.get(end, y + 1));
+        }
+    }
+
+    #[test]
+    fn secondary_axis_labels_on_the_right() {
+        let chart = LineChart::new()
+            .series(&[(0.0, 0.0), (10.0, 10.0)])
+            .series(&[(0.0, 1000.0), (10.0, 0.0)])
+            .secondary();
+        let lines = rows(&chart, 60, 40);
+        // Each label starts just right of the axis line, level with its tick.
+        let axis = lines[0].len() - "1000".len() - 1;
+        for &(row, label) in &[(0, "1000"), (4, "500"), (8, "0")] {
+            let text: String = lines[row][axis + 1..].iter().collect();
+            assert_eq!(text, label, "row {}", row);
+            assert!(lines[row][axis] != ' ');
+        }
+        assert!((1..8)
+            .filter(|&r| r != 4)
+            .all(|r| lines[r].len() == axis + 1));
+        // The primary axis keeps its own labels on the left.
+        let left: String = lines[0][..2].iter().collect();
+        assert_eq!(left, "10");
+
+        // Without a series plotted against it, the secondary axis isn’t drawn.
+        let plain = LineChart::new().series(&[(0.0, 0.0), (10.0, 10.0)]);
+        let unused = plain.clone().secondary_axis(Axis::new().range(0.0, 5.0));
+        assert_eq!(rows(&unused, 60, 40), rows(&plain, 60, 40));
+        assert!(rows(&plain, 60, 40)[0].len() > axis + 1);
+    }
+}
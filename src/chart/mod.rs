@@ -0,0 +1,313 @@
+//! Charts drawn with Braille characters.
+//!
+//! Each chart is configured with builder methods and then drawn with `render`, which returns a
+//! new `Canvas` of the requested size (in pixels) holding the plot along with its axes and
+//! labels.
+
+use std::fmt;
+use std::rc::Rc;
+
+use super::{Canvas, Palette, PixelColor};
+
+mod annotation;
+pub use self::annotation::Annotation;
+
+mod boxplot;
+pub use self::boxplot::{BoxPlot, BoxStats};
+
+mod candlestick;
+pub use self::candlestick::Candlestick;
+
+mod contour;
+pub use self::contour::Contour;
+
+mod line;
+pub use self::line::{ErrorBar, LineChart};
+
+mod polar;
+pub use self::polar::PolarPlot;
+
+mod stacked;
+pub use self::stacked::StackedArea;
+
+mod ticks;
+pub use self::ticks::nice_ticks;
+
+mod time;
+pub use self::time::timestamp;
+
+/// The configuration of a chart axis.
+#[derive(Clone, Default)]
+pub struct Axis {
+    range: Option<(f64, f64)>,
+    ticks: Option<usize>,
+    formatter: Option<Rc<dyn Fn(f64) -> String>>,
+    time: bool,
+}
+
+impl fmt::Debug for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Axis")
+            .field("range", &self.range)
+            .field("ticks", &self.ticks)
+            .field("formatter", &self.formatter.as_ref().map(|_| ".."))
+            .field("time", &self.time)
+            .finish()
+    }
+}
+
+impl Axis {
+    /// Creates a new `Axis` whose range is fitted to the data, with five labelled ticks.
+    pub fn new() -> Axis {
+        Axis::default()
+    }
+
+    /// Fixes the range of the `Axis` instead of fitting it to the data, and returns it for use
+    /// again.
+    pub fn range(mut self, min: f64, max: f64) -> Axis {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Sets the number of labelled ticks along the `Axis`, and returns it for use again.
+    ///
+    /// Ticks along a fixed range divide it evenly. Otherwise they are chosen with `nice_ticks`
+    /// to fall on round numbers, the range is widened to the first and last of them, and the
+    /// number is taken as an upper limit rather than an exact count.
+    pub fn ticks(mut self, ticks: usize) -> Axis {
+        self.ticks = Some(ticks);
+        self
+    }
+
+    /// Sets the function used to turn tick values into labels, and returns the `Axis` for use
+    /// again.
+    ///
+    /// By default, labels show the value rounded to at most two decimal places; a formatter
+    /// can be used to add units, percentages, currency symbols and so on.
+    pub fn formatter<F>(mut self, formatter: F) -> Axis
+    where
+        F: Fn(f64) -> String + 'static,
+    {
+        self.formatter = Some(Rc::new(formatter));
+        self
+    }
+
+    /// Treats the values along the `Axis` as Unix timestamps in seconds (see `timestamp`), and
+    /// returns it for use again.
+    ///
+    /// Ticks are then placed at round intervals of time, such as every 15 minutes or every
+    /// day, and labelled with the time or date in UTC. The number of ticks is taken as an upper
+    /// limit rather than an exact count.
+    pub fn time(mut self) -> Axis {
+        self.time = true;
+        self
+    }
+
+    /// Returns the range of the axis for data spanning `min` to `max`, widened to round
+    /// numbers at both ends unless the range is fixed.
+    fn bounds(&self, min: f64, max: f64) -> (f64, f64) {
+        let (min, max) = self.exact_bounds(min, max);
+        if self.range.is_some() || self.time {
+            return (min, max);
+        }
+        let ticks = ticks::nice_ticks(min, max, self.ticks.unwrap_or(5));
+        match (ticks.first(), ticks.last()) {
+            (Some(&first), Some(&last)) if first < last => (first, last),
+            _ => (min, max),
+        }
+    }
+
+    /// Returns the range of the axis for data spanning exactly `min` to `max`, such as the
+    /// indices of a grid.
+    fn exact_bounds(&self, min: f64, max: f64) -> (f64, f64) {
+        let (min, max) = self.range.unwrap_or((min, max));
+        if min < max {
+            return (min, max);
+        }
+        let mid = if min.is_finite() { min } else { 0.0 };
+        (mid - 1.0, mid + 1.0)
+    }
+
+    fn tick_values(&self, (min, max): (f64, f64)) -> Vec<f64> {
+        let ticks = self.ticks.unwrap_or(5);
+        if self.range.is_none() && ticks >= 2 {
+            let slack = (max - min) * 1e-9;
+            let mut values = ticks::nice_ticks(min, max, ticks);
+            values.retain(|&v| v >= min - slack && v <= max + slack);
+            return values;
+        }
+        match ticks {
+            0 => Vec::new(),
+            1 => vec![min],
+            _ => (0..ticks)
+                .map(|i| min + (max - min) * i as f64 / (ticks - 1) as f64)
+                .collect(),
+        }
+    }
+
+    /// Returns the ticks of the axis across `bounds`, along with their labels.
+    fn labels(&self, bounds: (f64, f64)) -> Vec<(f64, String)> {
+        if self.time {
+            let (ticks, interval) = time::ticks(bounds, self.ticks.unwrap_or(5));
+            return ticks
+                .into_iter()
+                .map(|v| match self.formatter {
+                    Some(ref formatter) => (v, formatter(v)),
+                    None => (v, time::format(v, interval)),
+                })
+                .collect();
+        }
+        self.tick_values(bounds)
+            .into_iter()
+            .map(|v| (v, self.label(v)))
+            .collect()
+    }
+
+    fn label(&self, value: f64) -> String {
+        match self.formatter {
+            Some(ref formatter) => formatter(value),
+            None => format_number(value),
+        }
+    }
+}
+
+/// A series of data points plotted by a chart, with an optional colour.
+#[derive(Clone, Debug)]
+struct Series {
+    points: Vec<(f64, f64)>,
+    color: Option<PixelColor>,
+    /// The error bars of each point, in the same order. Only `LineChart` draws them.
+    errors: Vec<line::ErrorBar>,
+    /// Whether the series is plotted against the secondary y axis on the right, which only
+    /// `LineChart` has.
+    secondary: bool,
+}
+
+impl Series {
+    fn new(points: &[(f64, f64)], color: Option<PixelColor>) -> Series {
+        Series {
+            points: points.to_vec(),
+            color,
+            errors: Vec::new(),
+            secondary: false,
+        }
+    }
+
+    /// Returns the colour of the series, which is the colour at `index` of `palette` if the
+    /// series doesn’t have one of its own.
+    fn color(&self, palette: Option<&Palette>, index: usize) -> Option<PixelColor> {
+        self.color.or_else(|| palette.map(|p| p.get(index)))
+    }
+}
+
+/// Draws a line in the given colour, or without a colour if there is none.
+fn draw_line(canvas: &mut Canvas, color: Option<PixelColor>, x1: u32, y1: u32, x2: u32, y2: u32) {
+    match color {
+        Some(color) => canvas.line_colored(x1, y1, x2, y2, color),
+        None => canvas.line(x1, y1, x2, y2),
+    }
+}
+
+/// The region of a `Canvas`, in pixels, that a chart plots its data into.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+}
+
+impl Frame {
+    /// Lays out a plot of `width` by `height` pixels, leaving `left` pixels for the y axis
+    /// labels and `below` pixels for anything drawn under the x axis.
+    fn new(width: u32, height: u32, left: u32, below: u32) -> Frame {
+        let right = width.saturating_sub(1).max(left + 1);
+        let bottom = height.saturating_sub(below + 1).max(1);
+        Frame {
+            left,
+            top: 0,
+            right,
+            bottom,
+        }
+    }
+
+    fn x(&self, value: f64, (min, max): (f64, f64)) -> u32 {
+        let t = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        self.left + (t * (self.right - self.left) as f64).round() as u32
+    }
+
+    fn y(&self, value: f64, (min, max): (f64, f64)) -> u32 {
+        let t = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        self.bottom - (t * (self.bottom - self.top) as f64).round() as u32
+    }
+}
+
+/// Returns the labels for the ticks of a y axis, along with the number of pixels needed to the
+/// left of the plot to fit them.
+fn y_labels(axis: &Axis, bounds: (f64, f64)) -> (Vec<(f64, String)>, u32) {
+    let labels = axis.labels(bounds);
+    let width = labels
+        .iter()
+        .map(|(_, l)| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    (labels, (width as u32 + 1) * 2)
+}
+
+/// Draws the axis lines along the left and bottom edges of `frame`, with the given labels to
+/// the left of the y axis.
+fn draw_axes(canvas: &mut Canvas, frame: Frame, labels: &[(f64, String)], bounds: (f64, f64)) {
+    canvas.line(frame.left, frame.top, frame.left, frame.bottom);
+    canvas.line(frame.left, frame.bottom, frame.right, frame.bottom);
+    let width = frame.left / 2 - 1;
+    for (value, label) in labels {
+        let y = frame.y(*value, bounds);
+        let x = (width - label.chars().count() as u32) * 2;
+        canvas.text(x, y, u32::MAX, label);
+    }
+}
+
+/// Draws a second y axis line along the right edge of `frame`, with the given labels to the
+/// right of it.
+fn draw_right_axis(
+    canvas: &mut Canvas,
+    frame: Frame,
+    labels: &[(f64, String)],
+    bounds: (f64, f64),
+) {
+    canvas.line(frame.right, frame.top, frame.right, frame.bottom);
+    let x = (frame.right / 2 + 1) * 2;
+    for (value, label) in labels {
+        canvas.text(x, frame.y(*value, bounds), u32::MAX, label);
+    }
+}
+
+/// Draws the given labels along the bottom of `frame`, skipping any that would overlap the one
+/// before.
+fn draw_x_labels(canvas: &mut Canvas, frame: Frame, labels: &[(f64, String)], bounds: (f64, f64)) {
+    let mut end = 0;
+    for (value, label) in labels {
+        let len = label.chars().count() as u32;
+        let start = (frame.x(*value, bounds) / 2).saturating_sub(len / 2);
+        if start < end {
+            continue;
+        }
+        canvas.text(start * 2, frame.bottom + 4, u32::MAX, label);
+        end = start + len + 1;
+    }
+}
+
+/// Draws `label` centred on the pixel column `x`, on the text row containing pixel row `y`.
+fn draw_centred(canvas: &mut Canvas, x: u32, y: u32, label: &str) {
+    let half = label.chars().count() as u32;
+    canvas.text((x / 2).saturating_sub(half / 2) * 2, y, u32::MAX, label);
+}
+
+fn format_number(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    if rounded == rounded.trunc() && rounded.abs() < 1e15 {
+        return format!("{}", rounded as i64);
+    }
+    let s = format!("{:.2}", rounded);
+    s.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
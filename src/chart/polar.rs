@@ -0,0 +1,118 @@
+use super::super::{Canvas, Palette, PixelColor};
+use super::{draw_line, Axis, Series};
+
+/// A plot of `(angle, radius)` samples on a circular axis.
+///
+/// Angles are compass bearings in degrees: `0` points up and angles increase clockwise. Each
+/// series is drawn as a closed outline through its samples, over rings marking the ticks of
+/// the radial axis and spokes radiating from the centre.
+#[derive(Clone, Debug)]
+pub struct PolarPlot {
+    series: Vec<Series>,
+    radial_axis: Axis,
+    spokes: u32,
+    palette: Option<Palette>,
+}
+
+impl Default for PolarPlot {
+    fn default() -> PolarPlot {
+        PolarPlot {
+            series: Vec::new(),
+            radial_axis: Axis::default(),
+            spokes: 8,
+            palette: None,
+        }
+    }
+}
+
+impl PolarPlot {
+    /// Creates a new, empty `PolarPlot` with eight spokes.
+    pub fn new() -> PolarPlot {
+        PolarPlot::default()
+    }
+
+    /// Adds a series of `(angle, radius)` samples, and returns the `PolarPlot` for use again.
+    pub fn series(mut self, points: &[(f64, f64)]) -> PolarPlot {
+        self.series.push(Series::new(points, None));
+        self
+    }
+
+    /// Adds a series of `(angle, radius)` samples drawn in the given colour, and returns the
+    /// `PolarPlot` for use again.
+    pub fn series_colored(mut self, points: &[(f64, f64)], color: PixelColor) -> PolarPlot {
+        self.series.push(Series::new(points, Some(color)));
+        self
+    }
+
+    /// Sets a palette to colour the series without a colour of their own from, in order, and
+    /// returns the `PolarPlot` for use again.
+    pub fn palette(mut self, palette: Palette) -> PolarPlot {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Sets the radial axis, and returns the `PolarPlot` for use again.
+    ///
+    /// The axis always starts from zero at the centre unless a range is given explicitly.
+    pub fn radial_axis(mut self, axis: Axis) -> PolarPlot {
+        self.radial_axis = axis;
+        self
+    }
+
+    /// Sets the number of spokes drawn from the centre, and returns the `PolarPlot` for use
+    /// again.
+    pub fn spokes(mut self, spokes: u32) -> PolarPlot {
+        self.spokes = spokes;
+        self
+    }
+
+    /// Draws the `PolarPlot` onto a new `Canvas` of `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Canvas {
+        let max = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|p| p.1))
+            .fold(0.0, f64::max);
+        let (min, max) = self.radial_axis.bounds(0.0, max);
+        let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+        let size = (cx.min(cy) - 1.0).max(1.0);
+        let point = |angle: f64, radius: f64| {
+            let r = ((radius - min) / (max - min)).max(0.0) * size;
+            let (sin, cos) = angle.to_radians().sin_cos();
+            (
+                (cx + r * sin).round().max(0.0) as u32,
+                (cy - r * cos).round().max(0.0) as u32,
+            )
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        for i in 0..self.spokes {
+            let (x, y) = point(360.0 * i as f64 / self.spokes as f64, max);
+            canvas.line(cx as u32, cy as u32, x, y);
+        }
+        for (value, label) in self.radial_axis.labels((min, max)) {
+            if value <= min {
+                continue;
+            }
+            let mut last = point(0.0, value);
+            for step in 1..=72 {
+                let next = point(step as f64 * 5.0, value);
+                canvas.line(last.0, last.1, next.0, next.1);
+                last = next;
+            }
+            let (x, y) = point(0.0, value);
+            canvas.text(x + 2, y, u32::MAX, &label);
+        }
+
+        for (i, series) in self.series.iter().enumerate() {
+            let color = series.color(self.palette.as_ref(), i);
+            let points = &series.points;
+            for (&(a1, r1), &(a2, r2)) in points.iter().zip(points.iter().cycle().skip(1)) {
+                let (x1, y1) = point(a1, r1);
+                let (x2, y2) = point(a2, r2);
+                draw_line(&mut canvas, color, x1, y1, x2, y2);
+            }
+        }
+        canvas
+    }
+}
@@ -0,0 +1,121 @@
+use super::super::{Canvas, Palette, PixelColor};
+use super::{annotation, draw_axes, draw_x_labels, y_labels, Annotation, Axis, Frame, Series};
+
+/// A chart stacking series of `(x, y)` points on top of each other, filling the area between
+/// each series and the one below it.
+///
+/// Every series should have the same x values in the same order; the x values of the first
+/// series are used for all of them. Series without a colour of their own are given one of a
+/// set of distinct colours.
+#[derive(Clone, Debug, Default)]
+pub struct StackedArea {
+    series: Vec<Series>,
+    x_axis: Axis,
+    y_axis: Axis,
+    annotations: Vec<(Annotation, Option<PixelColor>)>,
+    palette: Palette,
+}
+
+impl StackedArea {
+    /// Creates a new, empty `StackedArea` chart.
+    pub fn new() -> StackedArea {
+        StackedArea::default()
+    }
+
+    /// Adds a series of `(x, y)` points on top of the ones already added, and returns the
+    /// `StackedArea` for use again.
+    pub fn series(mut self, points: &[(f64, f64)]) -> StackedArea {
+        self.series.push(Series::new(points, None));
+        self
+    }
+
+    /// Adds a series of `(x, y)` points filled in the given colour, and returns the
+    /// `StackedArea` for use again.
+    pub fn series_colored(mut self, points: &[(f64, f64)], color: PixelColor) -> StackedArea {
+        self.series.push(Series::new(points, Some(color)));
+        self
+    }
+
+    /// Sets the palette to colour the series without a colour of their own from, in order, and
+    /// returns the `StackedArea` chart for use again.
+    pub fn palette(mut self, palette: Palette) -> StackedArea {
+        self.palette = palette;
+        self
+    }
+
+    /// Adds an annotation, such as a threshold line, and returns the `StackedArea` chart for use
+    /// again.
+    ///
+    /// Lines and bands are drawn under the data, in the order they were added, and text is
+    /// drawn over it.
+    pub fn annotate(mut self, annotation: Annotation) -> StackedArea {
+        self.annotations.push((annotation, None));
+        self
+    }
+
+    /// Adds an annotation drawn in the given colour, and returns the `StackedArea` chart for use
+    /// again.
+    pub fn annotate_colored(mut self, annotation: Annotation, color: PixelColor) -> StackedArea {
+        self.annotations.push((annotation, Some(color)));
+        self
+    }
+
+    /// Sets the x axis of the `StackedArea` chart, and returns it for use again.
+    pub fn x_axis(mut self, axis: Axis) -> StackedArea {
+        self.x_axis = axis;
+        self
+    }
+
+    /// Sets the y axis of the `StackedArea` chart, and returns it for use again.
+    ///
+    /// The axis always starts from zero unless a range is given explicitly.
+    pub fn y_axis(mut self, axis: Axis) -> StackedArea {
+        self.y_axis = axis;
+        self
+    }
+
+    /// Draws the `StackedArea` chart onto a new `Canvas` of `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Canvas {
+        let xs: Vec<f64> = self
+            .series
+            .first()
+            .map_or(Vec::new(), |s| s.points.iter().map(|p| p.0).collect());
+        let mut totals = vec![vec![0.0; xs.len()]];
+        for series in &self.series {
+            let below = totals.last().unwrap().clone();
+            let layer = below
+                .iter()
+                .enumerate()
+                .map(|(i, total)| total + series.points.get(i).map_or(0.0, |p| p.1))
+                .collect();
+            totals.push(layer);
+        }
+
+        let flat = totals.iter().flat_map(|t| t.iter().cloned());
+        let (ymin, ymax) = flat.fold((0.0f64, 0.0f64), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        let xmin = xs.iter().cloned().fold(f64::INFINITY, f64::min);
+        let xmax = xs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let xbounds = self.x_axis.bounds(xmin, xmax);
+        let ybounds = self.y_axis.bounds(ymin, ymax);
+        let (labels, left) = y_labels(&self.y_axis, ybounds);
+        let frame = Frame::new(width, height, left, 4);
+
+        let mut canvas = Canvas::new(width, height);
+        draw_axes(&mut canvas, frame, &labels, ybounds);
+        draw_x_labels(&mut canvas, frame, &self.x_axis.labels(xbounds), xbounds);
+        let bounds = (xbounds, ybounds);
+        annotation::draw(&mut canvas, frame, bounds, &self.annotations, false);
+        for (i, series) in self.series.iter().enumerate() {
+            let color = series.color.unwrap_or_else(|| self.palette.get(i));
+            let top = xs.iter().zip(&totals[i + 1]);
+            let bottom = xs.iter().zip(&totals[i]).rev();
+            let polygon: Vec<(u32, u32)> = top
+                .chain(bottom)
+                .map(|(&x, &y)| (frame.x(x, xbounds), frame.y(y, ybounds)))
+                .collect();
+            canvas.fill_polygon_colored(&polygon, color);
+        }
+        annotation::draw(&mut canvas, frame, bounds, &self.annotations, true);
+        canvas
+    }
+}
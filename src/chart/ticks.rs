@@ -0,0 +1,100 @@
+//! Tick selection at round numbers for axes showing plain values.
+
+/// Chooses ticks at a round interval covering `min` to `max`, with at most `count` of them,
+/// and returns them in order.
+///
+/// The interval between ticks is 1, 2 or 5 times a power of ten, the smallest of those that
+/// needs no more than `count` ticks. The first tick is at or below `min` and the last at or
+/// above `max`, so the ticks also give a range that the values fit in with round numbers at
+/// both ends. Fewer than two ticks can’t cover a range, so a `count` of one gives only `min`
+/// and a `count` of zero gives none, and if `min` and `max` are the same it is the only tick.
+///
+/// A range around zero always needs at least three round ticks, one on either side of zero
+/// and one at it, so with a `count` of two the ticks are just `min` and `max`.
+pub fn nice_ticks(min: f64, max: f64, count: usize) -> Vec<f64> {
+    let (min, max) = (min.min(max), min.max(max));
+    if count < 2 || !min.is_finite() || !max.is_finite() {
+        return if count == 0 { Vec::new() } else { vec![min] };
+    }
+    if min == max {
+        return vec![min];
+    }
+    let rough = (max - min) / (count - 1) as f64;
+    let mut magnitude = 10f64.powf(rough.log10().floor());
+    // Once steps are larger than every value, the values lie on either side of zero or fit
+    // between two ticks, so there is no point in looking further.
+    let largest = min.abs().max(max.abs());
+    while magnitude.is_finite() && magnitude <= largest * 10.0 {
+        for &factor in &[1.0, 2.0, 5.0] {
+            let step = factor * magnitude;
+            let (first, last) = ((min / step).floor(), (max / step).ceil());
+            if last - first < count as f64 {
+                return (first as i64..=last as i64)
+                    .map(|k| tidy(k as f64 * step, magnitude))
+                    .collect();
+            }
+        }
+        magnitude *= 10.0;
+    }
+    vec![min, max]
+}
+
+/// Rounds away the error that multiplying by a step can leave in a tick, such as
+/// `0.30000000000000004` for three steps of `0.1`.
+fn tidy(value: f64, magnitude: f64) -> f64 {
+    if magnitude >= 1.0 {
+        return value;
+    }
+    let scale = (1.0 / magnitude).round();
+    (value * scale).round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nice_ticks;
+
+    #[test]
+    fn round_steps() {
+        assert_eq!(nice_ticks(3.2719, 47.9, 5), [0.0, 20.0, 40.0, 60.0]);
+        assert_eq!(nice_ticks(0.0, 30.0, 5), [0.0, 10.0, 20.0, 30.0]);
+        assert_eq!(
+            nice_ticks(-3.0, 1234.0, 8),
+            [-500.0, 0.0, 500.0, 1000.0, 1500.0]
+        );
+        assert_eq!(nice_ticks(0.1, 0.7, 5), [0.0, 0.2, 0.4, 0.6, 0.8]);
+    }
+
+    #[test]
+    fn covers_range_within_count() {
+        for &(min, max) in &[(-1.0, 1.0), (0.013, 0.97), (-250.0, -3.5), (1e-9, 3e-9)] {
+            for count in 2..12 {
+                let ticks = nice_ticks(min, max, count);
+                assert!(
+                    ticks.len() <= count,
+                    "{:?} {} {:?}",
+                    (min, max),
+                    count,
+                    ticks
+                );
+                assert!(ticks.iter().all(|t| t.is_finite()));
+                assert!(ticks[0] <= min && *ticks.last().unwrap() >= max);
+            }
+        }
+    }
+
+    #[test]
+    fn two_ticks_around_zero() {
+        assert_eq!(nice_ticks(-1.0, 1.0, 2), [-1.0, 1.0]);
+        assert_eq!(nice_ticks(-3.0, 7.0, 2), [-3.0, 7.0]);
+        assert_eq!(nice_ticks(3.0, 7.0, 2), [0.0, 10.0]);
+    }
+
+    #[test]
+    fn degenerate() {
+        assert_eq!(nice_ticks(1.0, 5.0, 0), []);
+        assert_eq!(nice_ticks(1.0, 5.0, 1), [1.0]);
+        assert_eq!(nice_ticks(2.0, 2.0, 5), [2.0]);
+        assert_eq!(nice_ticks(7.0, 3.0, 3), nice_ticks(3.0, 7.0, 3));
+        assert_eq!(nice_ticks(-f64::MAX, f64::MAX, 2), [-f64::MAX, f64::MAX]);
+    }
+}
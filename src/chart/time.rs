@@ -0,0 +1,200 @@
+//! Tick selection and labelling for axes showing Unix timestamps.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The intervals considered for ticks on a time axis, in seconds.
+static INTERVALS: [f64; 20] = [
+    1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0, 7200.0,
+    10800.0, 21600.0, 43200.0, 86400.0, 172800.0, 604800.0,
+];
+
+/// The intervals considered for longer spans, in months. Ticks this far apart fall on the
+/// first of a month rather than at multiples of the interval, since months and years vary in
+/// length.
+static MONTHS: [i64; 3] = [1, 3, 12];
+
+/// The average length of a month in the Gregorian calendar, in seconds.
+const MONTH: f64 = 2629746.0;
+
+/// Converts a `SystemTime` into the number of seconds since the Unix epoch, as used by time
+/// axes.
+pub fn timestamp(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+/// Chooses ticks at a round interval across `min` to `max`, aiming for at most `count` of
+/// them, and returns them along with the interval chosen.
+///
+/// Intervals of a month or more are returned as a whole number of average months, and their
+/// ticks fall on the first of every month, every quarter or every few years.
+pub fn ticks((min, max): (f64, f64), count: usize) -> (Vec<f64>, f64) {
+    let count = count.max(1) as f64;
+    if let Some(interval) = INTERVALS
+        .iter()
+        .cloned()
+        .find(|&i| (max - min) / i <= count)
+    {
+        let first = (min / interval).ceil() as i64;
+        let last = (max / interval).floor() as i64;
+        return (
+            (first..=last).map(|k| k as f64 * interval).collect(),
+            interval,
+        );
+    }
+    let months = MONTHS
+        .iter()
+        .cloned()
+        .find(|&m| (max - min) / (m as f64 * MONTH) <= count)
+        .unwrap_or_else(|| ((max - min) / count / (12.0 * MONTH)).ceil() as i64 * 12);
+    // Months are counted from year 0, so that every few years lands on round years.
+    let mut month = month_of(min);
+    if month_start(month) < min {
+        month += 1;
+    }
+    month += (months - month.rem_euclid(months)) % months;
+    let mut ticks = Vec::new();
+    while month_start(month) <= max {
+        ticks.push(month_start(month));
+        month += months;
+    }
+    (ticks, months as f64 * MONTH)
+}
+
+/// Formats a timestamp (in UTC) with as much precision as ticks spaced `interval` apart need.
+pub fn format(timestamp: f64, interval: f64) -> String {
+    let secs = timestamp.floor() as i64;
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let (h, m, s) = (rem / 3600, rem / 60 % 60, rem % 60);
+    let (year, month, day) = civil_from_days(days);
+    if interval < 60.0 {
+        format!("{:02}:{:02}:{:02}", h, m, s)
+    } else if interval < 86400.0 {
+        format!("{:02}:{:02}", h, m)
+    } else if interval < MONTH {
+        format!("{:02}-{:02}", month, day)
+    } else if interval < 12.0 * MONTH {
+        format!("{}-{:02}", year, month)
+    } else {
+        format!("{}", year)
+    }
+}
+
+/// Converts a number of days since the Unix epoch into a `(year, month, day)` date in the
+/// proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts a `(year, month, day)` date in the proleptic Gregorian calendar into a number of
+/// days since the Unix epoch, undoing `civil_from_days`.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Returns the month that a timestamp falls in, counted from January of year 0.
+fn month_of(timestamp: f64) -> i64 {
+    let (year, month, _) = civil_from_days((timestamp / 86400.0).floor() as i64);
+    year * 12 + month as i64 - 1
+}
+
+/// Returns the timestamp of the start of a month counted as by `month_of`.
+fn month_start(month: i64) -> f64 {
+    let days = days_from_civil(month.div_euclid(12), month.rem_euclid(12) as u32 + 1, 1);
+    days as f64 * 86400.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{civil_from_days, days_from_civil, format, ticks};
+
+    fn date(year: i64, month: u32, day: u32) -> f64 {
+        days_from_civil(year, month, day) as f64 * 86400.0
+    }
+
+    fn labels(min: f64, max: f64, count: usize) -> Vec<String> {
+        let (ticks, interval) = ticks((min, max), count);
+        ticks.iter().map(|&t| format(t, interval)).collect()
+    }
+
+    #[test]
+    fn civil_round_trip() {
+        for &days in &[-719468, -1, 0, 59, 11016, 20440, 2932896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn short_intervals() {
+        let (t, interval) = ticks((0.0, 50.0), 5);
+        assert_eq!(
+            (t, interval),
+            (vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0], 10.0)
+        );
+        assert_eq!(
+            labels(3600.0, 4.0 * 3600.0, 3),
+            ["01:00", "02:00", "03:00", "04:00"]
+        );
+    }
+
+    #[test]
+    fn months_start_on_the_first() {
+        let (t, _) = ticks((date(2025, 1, 10), date(2025, 6, 20)), 6);
+        let firsts: Vec<_> = (2..7).map(|m| date(2025, m, 1)).collect();
+        assert_eq!(t, firsts);
+        assert_eq!(
+            labels(date(2025, 1, 10), date(2025, 6, 20), 6),
+            ["2025-02", "2025-03", "2025-04", "2025-05", "2025-06"]
+        );
+    }
+
+    #[test]
+    fn quarters() {
+        assert_eq!(
+            labels(date(2025, 6, 1), date(2026, 6, 1), 5),
+            ["2025-07", "2025-10", "2026-01", "2026-04"]
+        );
+    }
+
+    #[test]
+    fn years_start_in_january() {
+        let (t, _) = ticks((date(2020, 1, 1), date(2026, 6, 1)), 5);
+        assert_eq!(
+            t,
+            [
+                date(2020, 1, 1),
+                date(2022, 1, 1),
+                date(2024, 1, 1),
+                date(2026, 1, 1)
+            ]
+        );
+        assert_eq!(
+            labels(date(2020, 1, 1), date(2026, 6, 1), 5),
+            ["2020", "2022", "2024", "2026"]
+        );
+        assert_eq!(
+            labels(date(1969, 3, 1), date(1972, 3, 1), 5),
+            ["1970", "1971", "1972"]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Parsing of colours from strings.
+
+use std::error::Error;
+use std::fmt;
+
+use super::PixelColor;
+
+/// An error returned when a string can’t be parsed as a colour.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseColorError {
+    color: String,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid colour: `{}`", self.color)
+    }
+}
+
+impl Error for ParseColorError {}
+
+/// Parses a colour from a string.
+///
+/// The string can be a hexadecimal colour (`"#ff8000"`, `"ff8000"` or the short form `"#f80"`),
+/// one of the terminal colour names understood by `colored` (`"red"`, `"bright blue"` or
+/// `"bright_blue"`, …), or one of a selection of CSS colour names such as `"orange"`, which are
+/// given as true colours.
+pub fn parse_color(s: &str) -> Result<PixelColor, ParseColorError> {
+    let s = s.trim();
+    let err = || ParseColorError {
+        color: s.to_owned(),
+    };
+
+    if let Ok(color) = s.replace('_', " ").parse() {
+        return Ok(color);
+    }
+    let name = s.to_lowercase();
+    if let Some(&(_, (r, g, b))) = CSS_COLORS.iter().find(|&&(n, _)| n == name) {
+        return Ok(PixelColor::TrueColor { r, g, b });
+    }
+
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(err());
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap();
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    match hex.len() {
+        3 => Ok(PixelColor::TrueColor {
+            r: digit(0) * 0x11,
+            g: digit(1) * 0x11,
+            b: digit(2) * 0x11,
+        }),
+        6 => Ok(PixelColor::TrueColor {
+            r: channel(0),
+            g: channel(2),
+            b: channel(4),
+        }),
+        _ => Err(err()),
+    }
+}
+
+static CSS_COLORS: [(&str, (u8, u8, u8)); 26] = [
+    ("aqua", (0x00, 0xff, 0xff)),
+    ("beige", (0xf5, 0xf5, 0xdc)),
+    ("brown", (0xa5, 0x2a, 0x2a)),
+    ("chocolate", (0xd2, 0x69, 0x1e)),
+    ("coral", (0xff, 0x7f, 0x50)),
+    ("crimson", (0xdc, 0x14, 0x3c)),
+    ("fuchsia", (0xff, 0x00, 0xff)),
+    ("gold", (0xff, 0xd7, 0x00)),
+    ("gray", (0x80, 0x80, 0x80)),
+    ("grey", (0x80, 0x80, 0x80)),
+    ("indigo", (0x4b, 0x00, 0x82)),
+    ("khaki", (0xf0, 0xe6, 0x8c)),
+    ("lavender", (0xe6, 0xe6, 0xfa)),
+    ("lime", (0x00, 0xff, 0x00)),
+    ("maroon", (0x80, 0x00, 0x00)),
+    ("navy", (0x00, 0x00, 0x80)),
+    ("olive", (0x80, 0x80, 0x00)),
+    ("orange", (0xff, 0xa5, 0x00)),
+    ("pink", (0xff, 0xc0, 0xcb)),
+    ("salmon", (0xfa, 0x80, 0x72)),
+    ("silver", (0xc0, 0xc0, 0xc0)),
+    ("tan", (0xd2, 0xb4, 0x8c)),
+    ("teal", (0x00, 0x80, 0x80)),
+    ("tomato", (0xff, 0x63, 0x47)),
+    ("turquoise", (0x40, 0xe0, 0xd0)),
+    ("violet", (0xee, 0x82, 0xee)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::parse_color;
+    use PixelColor;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Result<PixelColor, ()> {
+        Ok(PixelColor::TrueColor { r, g, b })
+    }
+
+    fn parse(s: &str) -> Result<PixelColor, ()> {
+        parse_color(s).map_err(|_| ())
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse("#ff8000"), rgb(0xff, 0x80, 0x00));
+        assert_eq!(parse("FF8000"), rgb(0xff, 0x80, 0x00));
+        assert_eq!(parse(" #1a2B3c "), rgb(0x1a, 0x2b, 0x3c));
+        assert_eq!(parse("#f80"), rgb(0xff, 0x88, 0x00));
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("red"), Ok(PixelColor::Red));
+        assert_eq!(parse("bright_blue"), Ok(PixelColor::BrightBlue));
+        assert_eq!(parse("bright blue"), Ok(PixelColor::BrightBlue));
+        assert_eq!(parse("orange"), rgb(0xff, 0xa5, 0x00));
+        assert_eq!(parse("Tomato"), rgb(0xff, 0x63, 0x47));
+    }
+
+    #[test]
+    fn invalid() {
+        for s in &[
+            "", "#", "#ff80", "ff80000", "#12345", "#gg0000", "12345z", "#ff800é", "ré",
+        ] {
+            assert!(parse(s).is_err(), "{}", s);
+        }
+        assert_eq!(
+            parse_color("#gg0000").unwrap_err().to_string(),
+            "invalid colour: `#gg0000`"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Geometry on pixel coordinates.
+//!
+//! These helpers answer the questions interactive drawings keep asking, such as which shape is
+//! under the mouse or which line a point should snap to. Positions are pixels, with `y` growing
+//! downwards as on a `Canvas`.
+
+use std::f64;
+
+use super::{Point, Segment};
+
+/// Returns the point where two segments cross, or `None` if they don’t.
+///
+/// Segments that touch at an end count as crossing there. Segments that lie along the same
+/// line and overlap have no single crossing point, and the overlapping end closest to the
+/// start of `a` is returned.
+pub fn intersection(a: Segment, b: Segment) -> Option<(f64, f64)> {
+    let (p, r) = (float(a.0), sub(float(a.1), float(a.0)));
+    let (q, s) = (float(b.0), sub(float(b.1), float(b.0)));
+    let denom = cross(r, s);
+    let qp = sub(q, p);
+    if denom == 0.0 {
+        if cross(qp, r) != 0.0 {
+            // Parallel, but on different lines.
+            return None;
+        }
+        let len = dot(r, r);
+        if len == 0.0 {
+            // `a` is a single point.
+            return if distance_to_segment(a.0, b) == 0.0 {
+                Some(p)
+            } else {
+                None
+            };
+        }
+        // How far along `a` each end of `b` lies.
+        let t0 = dot(qp, r) / len;
+        let t1 = t0 + dot(s, r) / len;
+        let (lo, hi) = (t0.min(t1), t0.max(t1));
+        if hi < 0.0 || lo > 1.0 {
+            return None;
+        }
+        let t = lo.max(0.0);
+        return Some((p.0 + r.0 * t, p.1 + r.1 * t));
+    }
+    let t = cross(qp, s) / denom;
+    let u = cross(qp, r) / denom;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some((p.0 + r.0 * t, p.1 + r.1 * t))
+    } else {
+        None
+    }
+}
+
+/// Returns whether two segments cross or touch.
+pub fn segments_intersect(a: Segment, b: Segment) -> bool {
+    intersection(a, b).is_some()
+}
+
+/// Returns whether a point is inside the polygon with the given corners, by the even-odd rule
+/// like `Canvas::fill_polygon`.
+///
+/// Points on the outline of the polygon count as inside it.
+pub fn point_in_polygon(point: Point, polygon: &[Point]) -> bool {
+    let (x, y) = float(point);
+    let mut inside = false;
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        if distance_to_segment(point, (a, b)) == 0.0 {
+            return true;
+        }
+        let ((x1, y1), (x2, y2)) = (float(a), float(b));
+        if (y1 <= y) != (y2 <= y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Returns the shortest distance from a point to any point of a segment, in pixels.
+pub fn distance_to_segment(point: Point, segment: Segment) -> f64 {
+    let (pt, nearest) = (float(point), nearest_on_segment(point, segment));
+    let d = sub(pt, nearest);
+    dot(d, d).sqrt()
+}
+
+/// Returns the point on a segment closest to the given point, such as for snapping the point
+/// onto a line.
+pub fn nearest_on_segment(point: Point, segment: Segment) -> (f64, f64) {
+    let (a, b, p) = (float(segment.0), float(segment.1), float(point));
+    let ab = sub(b, a);
+    let len = dot(ab, ab);
+    if len == 0.0 {
+        return a;
+    }
+    let t = (dot(sub(p, a), ab) / len).clamp(0.0, 1.0);
+    (a.0 + ab.0 * t, a.1 + ab.1 * t)
+}
+
+/// Returns the direction from one point to another, in degrees between `0` and `360`.
+///
+/// Angles are measured clockwise from the right, like a `Turtle`’s rotation, so a `Turtle` at
+/// `from` turned to this angle faces `to`. The angle from a point to itself is `0`.
+pub fn angle(from: Point, to: Point) -> f64 {
+    let (dx, dy) = sub(float(to), float(from));
+    dy.atan2(dx).to_degrees().rem_euclid(360.0)
+}
+
+/// Returns the angle at `vertex` between the lines to `a` and to `b`, in degrees between `0`
+/// and `180`.
+pub fn angle_between(a: Point, vertex: Point, b: Point) -> f64 {
+    let diff = (angle(vertex, a) - angle(vertex, b)).abs();
+    if diff > 180.0 {
+        360.0 - diff
+    } else {
+        diff
+    }
+}
+
+fn float((x, y): Point) -> (f64, f64) {
+    (x as f64, y as f64)
+}
+
+fn sub(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn dot(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn cross(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        angle, angle_between, distance_to_segment, intersection, nearest_on_segment,
+        point_in_polygon, segments_intersect,
+    };
+
+    #[test]
+    fn crossing_segments() {
+        assert_eq!(
+            intersection(((0, 0), (4, 4)), ((0, 4), (4, 0))),
+            Some((2.0, 2.0))
+        );
+        assert_eq!(intersection(((0, 0), (4, 0)), ((1, 2), (3, 1))), None);
+        // Lines that would cross, but not within both segments.
+        assert_eq!(intersection(((0, 0), (2, 2)), ((0, 8), (8, 0))), None);
+    }
+
+    #[test]
+    fn touching_ends() {
+        assert_eq!(
+            intersection(((0, 0), (4, 0)), ((4, 0), (4, 5))),
+            Some((4.0, 0.0))
+        );
+        assert_eq!(
+            intersection(((0, 0), (4, 0)), ((2, 0), (2, 3))),
+            Some((2.0, 0.0))
+        );
+        assert!(segments_intersect(((0, 0), (4, 4)), ((4, 4), (9, 1))));
+    }
+
+    #[test]
+    fn parallel_and_collinear() {
+        assert_eq!(intersection(((0, 0), (4, 0)), ((0, 1), (4, 1))), None);
+        assert_eq!(intersection(((0, 0), (4, 0)), ((5, 0), (9, 0))), None);
+        assert_eq!(
+            intersection(((0, 0), (4, 0)), ((4, 0), (9, 0))),
+            Some((4.0, 0.0))
+        );
+        // The overlap closest to the start of `a`, whichever way round `b` is.
+        assert_eq!(
+            intersection(((1, 0), (6, 0)), ((3, 0), (9, 0))),
+            Some((3.0, 0.0))
+        );
+        assert_eq!(
+            intersection(((6, 0), (1, 0)), ((3, 0), (9, 0))),
+            Some((6.0, 0.0))
+        );
+        assert_eq!(
+            intersection(((2, 2), (5, 5)), ((0, 0), (9, 9))),
+            Some((2.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn degenerate_segments() {
+        assert_eq!(
+            intersection(((2, 2), (2, 2)), ((0, 0), (4, 4))),
+            Some((2.0, 2.0))
+        );
+        assert_eq!(intersection(((2, 3), (2, 3)), ((0, 0), (4, 4))), None);
+        assert_eq!(
+            intersection(((0, 0), (4, 4)), ((3, 3), (3, 3))),
+            Some((3.0, 3.0))
+        );
+        assert_eq!(intersection(((0, 0), (4, 4)), ((5, 5), (5, 5))), None);
+        assert_eq!(
+            intersection(((1, 1), (1, 1)), ((1, 1), (1, 1))),
+            Some((1.0, 1.0))
+        );
+        assert_eq!(intersection(((1, 1), (1, 1)), ((2, 1), (2, 1))), None);
+    }
+
+    #[test]
+    fn polygons() {
+        let square = [(0, 0), (10, 0), (10, 10), (0, 10)];
+        assert!(point_in_polygon((5, 5), &square));
+        assert!(!point_in_polygon((11, 5), &square));
+        assert!(!point_in_polygon((5, 20), &square));
+        // The outline, corners included, counts as inside.
+        for &point in &[(0, 5), (10, 5), (5, 0), (5, 10), (0, 0), (10, 10)] {
+            assert!(point_in_polygon(point, &square), "{:?}", point);
+        }
+        // Level with a corner, which mustn’t be counted twice.
+        let diamond = [(5, 0), (10, 5), (5, 10), (0, 5)];
+        assert!(point_in_polygon((3, 5), &diamond));
+        assert!(!point_in_polygon((12, 5), &diamond));
+        // The even-odd rule leaves the middle of a pentagram empty.
+        let star = [(10, 0), (16, 19), (0, 7), (20, 7), (4, 19)];
+        assert!(!point_in_polygon((10, 10), &star));
+        assert!(point_in_polygon((10, 3), &star));
+        assert!(!point_in_polygon((1, 1), &[]));
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance_to_segment((2, 3), ((0, 0), (4, 0))), 3.0);
+        assert_eq!(distance_to_segment((7, 4), ((0, 0), (4, 0))), 5.0);
+        assert_eq!(distance_to_segment((0, 0), ((0, 0), (4, 0))), 0.0);
+        assert_eq!(distance_to_segment((3, 4), ((0, 0), (0, 0))), 5.0);
+        assert_eq!(nearest_on_segment((5, 1), ((0, 0), (4, 4))), (3.0, 3.0));
+        assert_eq!(nearest_on_segment((9, 9), ((0, 0), (4, 4))), (4.0, 4.0));
+        assert_eq!(nearest_on_segment((9, 9), ((2, 2), (2, 2))), (2.0, 2.0));
+    }
+
+    #[test]
+    fn angles() {
+        assert_eq!(angle((5, 5), (9, 5)), 0.0);
+        assert_eq!(angle((5, 5), (5, 9)), 90.0);
+        assert_eq!(angle((5, 5), (1, 5)), 180.0);
+        assert_eq!(angle((5, 5), (5, 1)), 270.0);
+        assert_eq!(angle((5, 5), (5, 5)), 0.0);
+        assert_eq!(angle_between((9, 5), (5, 5), (5, 1)), 90.0);
+        assert_eq!(angle_between((9, 6), (5, 5), (9, 4)).round(), 28.0);
+        assert_eq!(angle_between((1, 5), (5, 5), (9, 5)), 180.0);
+    }
+}
@@ -0,0 +1,62 @@
+//! Importing of greyscale images.
+//!
+//! Images are given as buffers of luma (brightness) values, one byte per pixel and row after
+//! row, so that any image library can be used to decode them.
+
+use super::Canvas;
+
+impl Canvas {
+    /// Creates a new `Canvas` from a greyscale image of `width` by `height` pixels, setting
+    /// each pixel that is darker than `threshold`.
+    ///
+    /// Missing pixels at the end of `luma` are left unset.
+    pub fn from_luma(width: u32, height: u32, luma: &[u8], threshold: u8) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if luma_at(luma, width, x, y).is_some_and(|l| l < threshold) {
+                    canvas.set(x, y);
+                }
+            }
+        }
+        canvas
+    }
+
+    /// Creates a new `Canvas` from the outlines of a greyscale image of `width` by `height`
+    /// pixels, like `from_luma`.
+    ///
+    /// The image is run through a Sobel edge detector first, and each pixel where the strength
+    /// of the edge (between `0` and `255`) is at least `threshold` is set. This turns photos
+    /// into clean line art rather than blotchy silhouettes.
+    pub fn from_luma_edges(width: u32, height: u32, luma: &[u8], threshold: u8) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        // Pixels past the edges of the image take the value of the nearest one inside it.
+        let at = |x: i64, y: i64| {
+            let x = x.clamp(0, width as i64 - 1) as u32;
+            let y = y.clamp(0, height as i64 - 1) as u32;
+            luma_at(luma, width, x, y).unwrap_or(255) as f32
+        };
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - 2.0 * at(x - 1, y)
+                    - at(x - 1, y + 1);
+                let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - 2.0 * at(x, y - 1)
+                    - at(x + 1, y - 1);
+                // The strongest possible edge has a magnitude of `4 * 255 * sqrt(2)`.
+                let strength = (gx * gx + gy * gy).sqrt() / (4.0 * 2f32.sqrt());
+                if strength >= threshold as f32 {
+                    canvas.set(x as u32, y as u32);
+                }
+            }
+        }
+        canvas
+    }
+}
+
+fn luma_at(luma: &[u8], width: u32, x: u32, y: u32) -> Option<u8> {
+    luma.get(y as usize * width as usize + x as usize).cloned()
+}
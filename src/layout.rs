@@ -0,0 +1,171 @@
+//! Laying out several canvases as panels of one.
+//!
+//! Panels are placed on whole characters, so that their pixels and colours are copied exactly,
+//! and gaps between them are measured in characters too.
+
+use std::cmp;
+
+use super::Canvas;
+
+/// Where a panel goes within the room given to it, when it is smaller than the others beside it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Against the top or left edge.
+    #[default]
+    Start,
+    /// Halfway between the edges, rounded towards the start.
+    Center,
+    /// Against the bottom or right edge.
+    End,
+}
+
+impl Align {
+    /// Returns how far into `room` characters a panel of `size` characters starts.
+    fn offset(self, size: u32, room: u32) -> u32 {
+        match self {
+            Align::Start => 0,
+            Align::Center => (room - size) / 2,
+            Align::End => room - size,
+        }
+    }
+}
+
+impl Canvas {
+    /// Creates a new `Canvas` with the given canvases side by side, from left to right, with
+    /// `gap` columns of characters between each, and `align` placing the shorter ones between
+    /// the top and bottom.
+    pub fn hstack(canvases: &[&Canvas], gap: u32, align: Align) -> Canvas {
+        Canvas::grid(canvases, canvases.len(), (gap, 0), align)
+    }
+
+    /// Creates a new `Canvas` with the given canvases stacked from top to bottom, with `gap`
+    /// rows of characters between each, and `align` placing the narrower ones between the left
+    /// and right.
+    pub fn vstack(canvases: &[&Canvas], gap: u32, align: Align) -> Canvas {
+        Canvas::grid(canvases, 1, (0, gap), align)
+    }
+
+    /// Creates a new `Canvas` with the given canvases in a grid of `columns` columns, filled
+    /// row by row, with `gap` columns and rows of characters between them.
+    ///
+    /// Each column is as wide as its widest canvas and each row as tall as its tallest, and
+    /// `align` places the smaller canvases within that room in both directions. The size of
+    /// each canvas is taken to be its own width and height, or the extent of its contents if
+    /// larger. Only pixels, colours and letters are copied, so the new `Canvas` has the default
+    /// drawing modes.
+    pub fn grid(canvases: &[&Canvas], columns: usize, gap: (u32, u32), align: Align) -> Canvas {
+        let columns = cmp::max(columns, 1);
+        let sizes: Vec<(u32, u32)> = canvases
+            .iter()
+            .map(|c| {
+                let (cols, rows) = c.cell_bounds();
+                (cols as u32, rows as u32)
+            })
+            .collect();
+        let mut widths = vec![0; columns];
+        let mut heights = vec![0; sizes.len().div_ceil(columns)];
+        for (i, &(w, h)) in sizes.iter().enumerate() {
+            widths[i % columns] = cmp::max(widths[i % columns], w);
+            heights[i / columns] = cmp::max(heights[i / columns], h);
+        }
+        let starts = |sizes: &[u32], gap: u32| -> Vec<u32> {
+            let mut pos = 0;
+            sizes
+                .iter()
+                .map(|&size| {
+                    let start = pos;
+                    pos += size + gap;
+                    start
+                })
+                .collect()
+        };
+        let (lefts, tops) = (starts(&widths, gap.0), starts(&heights, gap.1));
+        let total = |sizes: &[u32], gap: u32| {
+            sizes.iter().sum::<u32>() + gap * (sizes.len() as u32).saturating_sub(1)
+        };
+
+        let mut canvas = Canvas::new(total(&widths, gap.0) * 2, total(&heights, gap.1) * 4);
+        for (i, (panel, &(w, h))) in canvases.iter().zip(&sizes).enumerate() {
+            let (col, row) = (i % columns, i / columns);
+            let x = lefts[col] + align.offset(w, widths[col]);
+            let y = tops[row] + align.offset(h, heights[row]);
+            canvas.blit(panel, x * 2, y * 4);
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Align;
+    use Canvas;
+
+    fn canvas(pixels: &[(u32, u32)]) -> Canvas {
+        pixels.iter().cloned().collect()
+    }
+
+    fn pixels(canvas: &Canvas) -> Vec<(u32, u32)> {
+        let mut pixels = Vec::new();
+        for y in 0..40 {
+            for x in 0..40 {
+                if canvas.get(x, y) {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    // Three characters by two, with a pixel in the middle of its last character.
+    fn a() -> Canvas {
+        canvas(&[(4, 5)])
+    }
+
+    // One character by three.
+    fn b() -> Canvas {
+        canvas(&[(0, 0), (1, 9)])
+    }
+
+    #[test]
+    fn hstack() {
+        let (a, b) = (a(), b());
+        let stacked = |align| pixels(&Canvas::hstack(&[&a, &b], 1, align));
+        assert_eq!(stacked(Align::Start), [(8, 0), (4, 5), (9, 9)]);
+        assert_eq!(stacked(Align::Center), [(8, 0), (4, 5), (9, 9)]);
+        assert_eq!(stacked(Align::End), [(8, 0), (4, 9), (9, 9)]);
+        assert_eq!(
+            pixels(&Canvas::hstack(&[&b, &a], 0, Align::End)),
+            [(0, 0), (1, 9), (6, 9)]
+        );
+    }
+
+    #[test]
+    fn vstack() {
+        let (a, b) = (a(), b());
+        let stacked = |align| pixels(&Canvas::vstack(&[&a, &b], 2, align));
+        assert_eq!(stacked(Align::Start), [(4, 5), (0, 16), (1, 25)]);
+        assert_eq!(stacked(Align::Center), [(4, 5), (2, 16), (3, 25)]);
+        assert_eq!(stacked(Align::End), [(4, 5), (4, 16), (5, 25)]);
+    }
+
+    #[test]
+    fn grid() {
+        let (a, b, c) = (a(), b(), canvas(&[(2, 2)]));
+        let grid = |align| Canvas::grid(&[&a, &b, &c], 2, (1, 1), align);
+        assert_eq!(
+            pixels(&grid(Align::Start)),
+            [(8, 0), (4, 5), (9, 9), (2, 18)]
+        );
+        assert_eq!(pixels(&grid(Align::End)), [(8, 0), (4, 9), (9, 9), (4, 18)]);
+        // Columns are three and one characters wide and rows three and one tall, with a gap
+        // of one between each.
+        let size = |c: &Canvas| {
+            (
+                c.frame().lines().count(),
+                c.frame().lines().next().unwrap().chars().count(),
+            )
+        };
+        assert_eq!(size(&grid(Align::Start)), size(&Canvas::new(10, 20)));
+        assert_eq!(pixels(&Canvas::grid(&[], 3, (1, 1), Align::Start)), []);
+    }
+}
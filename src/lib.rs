@@ -23,23 +23,103 @@
 //! ```
 use std::char;
 use std::cmp;
+use std::env;
+use std::error::Error;
 use std::f32;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::iter::FromIterator;
+use std::mem;
+use std::sync::Arc;
 
 extern crate fnv;
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHasher};
 
 extern crate colored;
 pub use colored::Color as PixelColor;
-use colored::Colorize;
 
-static PIXEL_MAP: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+mod autoscale;
+pub use autoscale::{terminal_size, Mapper};
+
+mod cells;
+use cells::Cells;
+
+pub mod chart;
+
+mod color;
+pub use color::{parse_color, ParseColorError};
+
+pub mod geometry;
+
+mod image;
+
+mod layout;
+pub use layout::Align;
+
+mod mouse;
+pub use mouse::Placement;
+
+mod ops;
+
+mod raster;
+pub use raster::{arc_points, ellipse_points, line_points, polygon_points};
+
+mod palette;
+pub use palette::Palette;
+
+mod record;
+pub use record::{Command, ParseCommandError};
+
+mod scene;
+pub use scene::{Scene, Shape, ShapeId};
+
+mod shared;
+pub use shared::SharedCanvas;
+
+mod style;
+pub use style::{AnsiStyle, Style};
+
+mod turtle3d;
+pub use turtle3d::{Camera, Turtle3D, Vec3};
+
+/// The position of a pixel on a `Canvas`, as `(x, y)`.
+pub type Point = (u32, u32);
+
+/// The end points of a line, as drawn by `Canvas::lines`.
+pub type Segment = (Point, Point);
+
+/// The bit standing for each pixel of a character cell, indexed by `[y % 4][x % 2]`.
+///
+/// The bits are those of the Unicode Braille patterns, so a cell with the dots `dots` set is
+/// drawn as `U+2800 + dots` unless the `Canvas` is given other glyphs with `set_glyphs`.
+pub static PIXEL_MAP: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
 
 /// A canvas object that can be used to draw to the terminal using Braille characters.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// Two canvases compare equal if they have the same dimensions and contents; drawing modes and
+/// recorded commands are not taken into account.
+#[derive(Clone, Debug)]
 pub struct Canvas {
-    chars: FnvHashMap<(u16, u16), (u8, char, bool, PixelColor)>,
+    chars: Cells,
     width: u16,
     height: u16,
+    log: Option<Vec<Command>>,
+    symmetry: Symmetry,
+    rainbow: Option<(f32, f32)>,
+    tolerance: f32,
+    aspect: f32,
+    wrap: bool,
+    color_mode: ColorMode,
+    color_reset: ColorReset,
+    color_depth: ColorDepth,
+    theme: Option<fn(PixelColor) -> PixelColor>,
+    glyphs: fn(u8) -> char,
+    palette: Palette,
+    indexed: FnvHashMap<(u16, u16), usize>,
+    indexing: Option<usize>,
+    cycle: usize,
+    style: Arc<dyn Style>,
 }
 
 impl Canvas {
@@ -49,92 +129,265 @@ impl Canvas {
     /// if a pixel is set outside the dimensions.
     pub fn new(width: u32, height: u32) -> Canvas {
         Canvas {
-            chars: FnvHashMap::default(),
+            chars: Cells::default(),
             width: (width / 2) as u16,
             height: (height / 4) as u16,
+            log: None,
+            symmetry: Symmetry::default(),
+            rainbow: None,
+            tolerance: raster::DEFAULT_TOLERANCE,
+            aspect: 1.0,
+            wrap: false,
+            color_mode: ColorMode::Auto,
+            color_reset: ColorReset::EachCell,
+            color_depth: ColorDepth::TrueColor,
+            theme: None,
+            glyphs: braille,
+            palette: Palette::default(),
+            indexed: FnvHashMap::default(),
+            indexing: None,
+            cycle: 0,
+            style: Arc::new(AnsiStyle),
+        }
+    }
+
+    /// Creates a new `Canvas` from a picture drawn with text, with a pixel set for every
+    /// character found in `set_chars`.
+    ///
+    /// Each character of `art` is one pixel and each line is one row of pixels; all other
+    /// characters are left empty. The `Canvas` is sized to fit the longest line.
+    pub fn from_text_art(art: &str, set_chars: &[char]) -> Canvas {
+        let mut width = 0;
+        let mut height = 0;
+        let mut points = Vec::new();
+        for (y, line) in art.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if set_chars.contains(&c) {
+                    points.push((x as u32, y as u32));
+                }
+                width = cmp::max(width, x as u32 + 1);
+            }
+            height = y as u32 + 1;
         }
+        let mut canvas = Canvas::new(width, height);
+        canvas.extend(points);
+        canvas
     }
 
     /// Clears the canvas.
     pub fn clear(&mut self) {
+        self.record(Command::Clear);
         self.chars.clear();
+        self.indexed.clear();
     }
 
     /// Sets a pixel at the specified coordinates.
     pub fn set(&mut self, x: u32, y: u32) {
-        let (row, col) = ((x / 2) as u16, (y / 4) as u16);
-        let a = self
-            .chars
-            .entry((row, col))
-            .or_insert((0, ' ', false, PixelColor::White));
-        a.0 |= PIXEL_MAP[y as usize % 4][x as usize % 2];
-        a.1 = ' ';
-        a.2 = false;
-        a.3 = PixelColor::White;
+        self.record(Command::Set { x, y });
+        self.paint(x, y, None);
     }
 
     /// Sets a pixel at the specified coordinates.
-    /// specifying the color of the braille char 
+    /// specifying the color of the braille char
     pub fn set_colored(&mut self, x: u32, y: u32, color: PixelColor) {
-        let (row, col) = ((x / 2) as u16, (y / 4) as u16);
-        let a = self
-            .chars
-            .entry((row, col))
-            .or_insert((0, ' ', false, PixelColor::White));
-        a.0 |= PIXEL_MAP[y as usize % 4][x as usize % 2];
-        a.1 = ' ';
-        a.2 = true;
-        a.3 = color;
+        self.record(Command::SetColored { x, y, color });
+        self.paint(x, y, Some(color));
+    }
+
+    /// Sets a pixel at the specified coordinates in the colour at `index` of the `Canvas`’s
+    /// palette.
+    ///
+    /// The pixel remembers the index it was drawn with, so it changes colour along with the
+    /// palette when that is rotated with `cycle_palette`.
+    pub fn set_indexed(&mut self, x: u32, y: u32, index: usize) {
+        let color = self.palette.get(index);
+        self.indexing = Some(index);
+        self.set_colored(x, y, color);
+        self.indexing = None;
     }
 
     /// Sets a letter at the specified coordinates.
+    ///
+    /// Wide letters, such as CJK characters and most emoji, take up two columns of the terminal
+    /// and so cover up the character to their right. Zero-width characters, such as combining
+    /// accents, can’t share a cell with the letter they belong to and are left out.
     pub fn set_char(&mut self, x: u32, y: u32, c: char) {
-        let (row, col) = ((x / 2) as u16, (y / 4) as u16);
-        let a = self
-            .chars
-            .entry((row, col))
-            .or_insert((0, ' ', false, PixelColor::White));
-        a.0 = 0;
-        a.1 = c;
-        a.2 = false;
-        a.3 = PixelColor::White;
+        self.record(Command::SetChar { x, y, c });
+        let (x, y) = self.wrap(x, y);
+        self.put_char(x, y, c);
     }
 
     /// Draws text at the specified coordinates (top-left of the text) up to max_width length
     pub fn text(&mut self, x: u32, y: u32, max_width: u32, text: &str) {
-        for (i, c) in text.chars().enumerate() {
-            let w = i as u32 * 2;
+        self.record(Command::Text {
+            x,
+            y,
+            max_width,
+            text: text.to_owned(),
+        });
+        let mut w = 0;
+        for c in text.chars() {
             if w > max_width {
                 return;
             }
-            self.set_char(x + w, y, c);
+            let (px, py) = self.wrap(x + w, y);
+            self.put_char(px, py, c);
+            w += char_width(c) * 2;
+        }
+    }
+
+    /// Draws text like `text`, breaking it into lines no wider than `max_width` pixels.
+    ///
+    /// Lines are broken between words where possible, and at newlines in the text. Each line
+    /// is drawn one row of characters (four pixels) below the one before.
+    pub fn text_wrapped(&mut self, x: u32, y: u32, max_width: u32, text: &str) {
+        for (i, line) in wrap_text(text, max_width).iter().enumerate() {
+            self.text(x, y + i as u32 * 4, max_width, line);
+        }
+    }
+
+    /// Returns the width and height in pixels that `text` takes up when drawn with `text`,
+    /// counting wide letters as two characters.
+    pub fn text_extent(text: &str) -> (u32, u32) {
+        if text.is_empty() {
+            return (0, 0);
+        }
+        (text.chars().map(|c| char_width(c) * 2).sum(), 4)
+    }
+
+    /// Returns the width and height in pixels that `text` takes up when drawn with
+    /// `text_wrapped`.
+    pub fn text_extent_wrapped(text: &str, max_width: u32) -> (u32, u32) {
+        let lines = wrap_text(text, max_width);
+        let width = lines.iter().map(|l| Canvas::text_extent(l).0).max();
+        (width.unwrap_or(0), lines.len() as u32 * 4)
+    }
+
+    /// Copies a packed buffer of pixels onto the `Canvas`, with its top-left corner at `(x, y)`.
+    ///
+    /// `data` holds rows of `width` pixels, one bit per pixel with the most significant bit of
+    /// each byte first, and each row starting on a new byte. Pixels whose bits are 1 are set and
+    /// those whose bits are 0 are deleted, so a whole frame can be replaced at once. The pixels
+    /// written lose their colours.
+    ///
+    /// Coordinates wrap as they do for `set` and `unset`, and pixels that are set are mirrored
+    /// like those set with `set`; as with `unset`, deleted pixels are not mirrored.
+    pub fn set_rows_from_bits(&mut self, x: u32, y: u32, width: u32, data: &[u8]) {
+        self.record(Command::SetRowsFromBits {
+            x,
+            y,
+            width,
+            data: data.to_vec(),
+        });
+        let stride = width.div_ceil(8) as usize;
+        if stride == 0 {
+            return;
+        }
+        let mut cells: FnvHashMap<(u16, u16), (u8, u8)> = FnvHashMap::default();
+        let mut set = Vec::new();
+        for (j, row) in data.chunks(stride).enumerate() {
+            for i in 0..width {
+                let on = row
+                    .get(i as usize / 8)
+                    .is_some_and(|b| b & (0x80 >> (i % 8)) != 0);
+                if on {
+                    set.push((x + i, y + j as u32));
+                }
+                let (px, py) = self.wrap(x + i, y + j as u32);
+                let bit = PIXEL_MAP[py as usize % 4][px as usize % 2];
+                let cell = cells
+                    .entry(((px / 2) as u16, (py / 4) as u16))
+                    .or_insert((0, 0));
+                cell.1 |= bit;
+                if on {
+                    cell.0 |= bit;
+                }
+            }
+        }
+        for (pos, (on, touched)) in cells {
+            let a = self.chars.entry(pos, (0, ' ', false, PixelColor::White));
+            if on != 0 {
+                a.1 = ' ';
+            }
+            a.0 = (a.0 & !touched) | on;
+            a.2 = false;
+            a.3 = PixelColor::White;
+            self.indexed.remove(&pos);
+        }
+        if self.symmetry.is_mirrored() {
+            for (px, py) in set {
+                self.paint(px, py, None);
+            }
+        }
+    }
+
+    /// Sets the pixels of a small bitmap, with its top-left corner at `(x, y)`.
+    ///
+    /// `rows` holds rows of `width` pixels one after another, with `true` for each pixel to
+    /// set; pixels that are `false` are left untouched. Each character is written once, so this
+    /// is much faster than setting the pixels one by one, but otherwise the pixels are set just
+    /// as `set` would, wrapping and mirroring included.
+    pub fn stamp(&mut self, x: u32, y: u32, width: u32, rows: &[bool]) {
+        self.record(Command::Stamp {
+            x,
+            y,
+            width,
+            rows: rows.to_vec(),
+        });
+        if width == 0 {
+            return;
+        }
+        let pixels = rows
+            .chunks(width as usize)
+            .enumerate()
+            .flat_map(|(j, row)| {
+                let on = row.iter().enumerate().filter(|&(_, &on)| on);
+                on.map(move |(i, _)| (x + i as u32, y + j as u32))
+            });
+        let pixels: Vec<(u32, u32)> = pixels.collect();
+        let mut cells: FnvHashMap<(u16, u16), u8> = FnvHashMap::default();
+        for &(px, py) in &pixels {
+            let (px, py) = self.wrap(px, py);
+            let bit = PIXEL_MAP[py as usize % 4][px as usize % 2];
+            *cells.entry(((px / 2) as u16, (py / 4) as u16)).or_insert(0) |= bit;
+        }
+        for (pos, dots) in cells {
+            self.paint_dots(pos, dots, None);
+        }
+        if self.symmetry.is_mirrored() {
+            for (px, py) in pixels {
+                self.paint(px, py, None);
+            }
         }
     }
 
     /// Deletes a pixel at the specified coordinates.
     pub fn unset(&mut self, x: u32, y: u32) {
+        self.record(Command::Unset { x, y });
+        let (x, y) = self.wrap(x, y);
         let (row, col) = ((x / 2) as u16, (y / 4) as u16);
         let a = self
             .chars
-            .entry((row, col))
-            .or_insert((0, ' ', false, PixelColor::White));
+            .entry((row, col), (0, ' ', false, PixelColor::White));
         a.0 &= !PIXEL_MAP[y as usize % 4][x as usize % 2];
     }
 
     /// Toggles a pixel at the specified coordinates.
     pub fn toggle(&mut self, x: u32, y: u32) {
+        self.record(Command::Toggle { x, y });
+        let (x, y) = self.wrap(x, y);
         let (row, col) = ((x / 2) as u16, (y / 4) as u16);
         let a = self
             .chars
-            .entry((row, col))
-            .or_insert((0, ' ', false, PixelColor::White));
+            .entry((row, col), (0, ' ', false, PixelColor::White));
         a.0 ^= PIXEL_MAP[y as usize % 4][x as usize % 2];
     }
 
     /// Detects whether the pixel at the given coordinates is set.
     pub fn get(&self, x: u32, y: u32) -> bool {
+        let (x, y) = self.wrap(x, y);
         let (row, col) = ((x / 2) as u16, (y / 4) as u16);
-        self.chars.get(&(row, col)).map_or(false, |a| {
+        self.chars.get(&(row, col)).is_some_and(|a| {
             let dot_index = PIXEL_MAP[y as usize % 4][x as usize % 2];
             a.0 & dot_index != 0
         })
@@ -145,235 +398,3257 @@ impl Canvas {
     /// Note that each row is actually four pixels high due to the fact that a single Braille
     /// character spans two by four pixels.
     pub fn rows(&self) -> Vec<String> {
-        let mut maxrow = self.width;
-        let mut maxcol = self.height;
-        for &(x, y) in self.chars.keys() {
-            if x > maxrow {
-                maxrow = x;
-            }
-            if y > maxcol {
-                maxcol = y;
-            }
+        let (maxrow, maxcol) = self.render_bounds();
+        self.render_cells(0, 0, maxrow, maxcol)
+    }
+
+    /// Returns an iterator over each row of the `Canvas`, like `rows`, drawing each row only
+    /// when it is reached.
+    ///
+    /// With `ColorReset::End`, each row resets the colour at its own end, so that rows can be
+    /// used on their own.
+    pub fn rows_iter<'a>(&'a self) -> impl Iterator<Item = String> + 'a {
+        let (maxrow, maxcol) = self.render_bounds();
+        (0..=maxcol).map(move |y| self.render_cells(0, y, maxrow, y).pop().unwrap_or_default())
+    }
+
+    /// Returns a `Vec` of the rows of characters from `y_start_row` up to (but not including)
+    /// `y_end_row`, without drawing the rest of the `Canvas`.
+    ///
+    /// Rows past the bottom of the `Canvas` are left out.
+    pub fn rows_range(&self, y_start_row: u32, y_end_row: u32) -> Vec<String> {
+        let (maxrow, maxcol) = self.render_bounds();
+        let end = cmp::min(y_end_row, maxcol as u32 + 1);
+        if y_start_row >= end {
+            return Vec::new();
         }
+        self.render_cells(0, y_start_row as u16, maxrow, (end - 1) as u16)
+    }
 
-        let mut result = Vec::with_capacity(maxcol as usize + 1);
-        for y in 0..=maxcol {
-            let mut row = String::with_capacity(maxrow as usize + 1);
-            for x in 0..=maxrow {
-                let cell =
-                    self.chars
-                        .get(&(x, y))
-                        .cloned()
-                        .unwrap_or((0, ' ', false, PixelColor::White));
-                match cell {
-                    (0, _, _, _) => row.push(cell.1),
-                    (_, _, false, _) => row.push(char::from_u32(0x2800 + cell.0 as u32).unwrap()),
-                    (_, _, true, _) => {
-                        row = format!(
-                            "{0}{1}",
-                            row,
-                            String::from(char::from_u32(0x2800 + cell.0 as u32).unwrap())
-                                .color(cell.3)
-                        )
-                    }
-                };
+    /// Returns a `Vec` of each row of the part of the `Canvas` between the pixel coordinates
+    /// `(min_x, min_y)` and `(max_x, max_y)`, inclusive.
+    ///
+    /// Since whole characters are drawn, the window is widened to the nearest character
+    /// boundaries.
+    pub fn rows_in(&self, min_x: u32, min_y: u32, max_x: u32, max_y: u32) -> Vec<String> {
+        self.render_cells(
+            (min_x / 2) as u16,
+            (min_y / 4) as u16,
+            (max_x / 2) as u16,
+            (max_y / 4) as u16,
+        )
+    }
+
+    /// Draws the part of the `Canvas` between the pixel coordinates `(min_x, min_y)` and
+    /// `(max_x, max_y)`, inclusive, to a `String` and returns it.
+    pub fn frame_in(&self, min_x: u32, min_y: u32, max_x: u32, max_y: u32) -> String {
+        self.rows_in(min_x, min_y, max_x, max_y).join("\n")
+    }
+
+    /// Draws the canvas to a `String` like `frame`, with rulers along the top and left edges
+    /// showing pixel coordinates.
+    ///
+    /// The top ruler labels the x coordinate of every fifth character, and the left ruler the
+    /// y coordinate of the top of every row.
+    pub fn frame_with_rulers(&self) -> String {
+        let rows = self.rows();
+        let gutter = ((rows.len() - 1) * 4).to_string().len();
+
+        let cols = self.render_bounds().0 as usize + 1;
+        let mut labels = String::new();
+        let mut ticks = String::new();
+        for col in 0..cols {
+            if col % 5 == 0 {
+                if col == 0 || labels.len() < col {
+                    labels.push_str(&" ".repeat(col - labels.len()));
+                    labels.push_str(&(col * 2).to_string());
+                }
+                ticks.push('|');
+            } else {
+                ticks.push(' ');
             }
-            result.push(row);
+        }
+
+        let mut result = format!("{:w$} {}\n{:w$} {}", "", labels, "", ticks, w = gutter);
+        for (i, row) in rows.iter().enumerate() {
+            result.push_str(&format!("\n{:>w$} {}", i * 4, row, w = gutter));
         }
         result
     }
 
+    /// Returns the characters making up each row of the `Canvas`, without any colours.
+    ///
+    /// The grid has the same dimensions as the output of `rows`, with characters covered up by
+    /// a wide letter to their left given as `'\0'`.
+    pub fn char_grid(&self) -> Vec<Vec<char>> {
+        let (maxrow, maxcol) = self.render_bounds();
+        (0..=maxcol)
+            .map(|y| {
+                (0..=maxrow)
+                    .map(|x| match self.chars.get(&(x, y)) {
+                        _ if self.covered(x, y) => '\0',
+                        Some(&(0, c, _, _)) => c,
+                        Some(&(dots, _, _, _)) => (self.glyphs)(dots),
+                        None => ' ',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the colour of each character of the `Canvas`, in a grid parallel to the one
+    /// returned by `char_grid`.
+    ///
+    /// Characters without a colour are `None`. The theme, if any, is applied, but the colour
+    /// mode is not taken into account.
+    pub fn color_grid(&self) -> Vec<Vec<Option<PixelColor>>> {
+        let (maxrow, maxcol) = self.render_bounds();
+        (0..=maxcol)
+            .map(|y| {
+                (0..=maxrow)
+                    .map(|x| match self.chars.get(&(x, y)) {
+                        Some(&(dots, _, true, color)) if dots != 0 && !self.covered(x, y) => {
+                            Some(self.shown_color((x, y), color))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     /// Draws the canvas to a `String` and returns it.
     pub fn frame(&self) -> String {
         self.rows().join("\n")
     }
 
-    /// Draws a line from `(x1, y1)` to `(x2, y2)` onto the `Canvas`.
-    pub fn line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
-        let xdiff = cmp::max(x1, x2) - cmp::min(x1, x2);
-        let ydiff = cmp::max(y1, y2) - cmp::min(y1, y2);
-        let xdir = if x1 <= x2 { 1 } else { -1 };
-        let ydir = if y1 <= y2 { 1 } else { -1 };
+    /// Draws the canvas `rows` rows of characters at a time, returning an iterator over the
+    /// pieces.
+    ///
+    /// Joined together, the pieces make up the same text as `frame`: each one but the last ends
+    /// with a newline. (With `ColorReset::End`, each piece also resets the colour at its own
+    /// end.) Only one piece is held in memory at a time, so huge frames can be sent somewhere
+    /// piece by piece without building the whole `String`.
+    pub fn frame_chunks<'a>(&'a self, rows: u32) -> impl Iterator<Item = String> + 'a {
+        let (maxrow, maxcol) = self.render_bounds();
+        let rows = cmp::max(rows, 1);
+        let total = maxcol as u32 + 1;
+        (0..total.div_ceil(rows)).map(move |i| {
+            let start = i * rows;
+            let end = cmp::min(start + rows, total);
+            let mut chunk = self
+                .render_cells(0, start as u16, maxrow, (end - 1) as u16)
+                .join("\n");
+            if end < total {
+                chunk.push('\n');
+            }
+            chunk
+        })
+    }
 
-        let r = cmp::max(xdiff, ydiff);
+    /// Writes the canvas to `out` like `frame`, drawing it `rows` rows of characters at a time
+    /// with `frame_chunks`.
+    pub fn write_frame<W: io::Write>(&self, out: &mut W, rows: u32) -> io::Result<()> {
+        for chunk in self.frame_chunks(rows) {
+            out.write_all(chunk.as_bytes())?;
+        }
+        Ok(())
+    }
 
-        for i in 0..=r {
-            let mut x = x1 as i32;
-            let mut y = y1 as i32;
+    /// Returns a `Vec` of each row of the `Canvas` turned 90° clockwise, so that its left edge
+    /// is at the top.
+    ///
+    /// This suits wide drawings, such as long time series, shown in a tall and narrow place.
+    /// Letters can’t be turned, so each is kept upright in the character its top-left corner
+    /// turns into.
+    pub fn rows_rotated(&self) -> Vec<String> {
+        self.rotated().rows()
+    }
 
-            if ydiff != 0 {
-                y += ((i * ydiff) / r) as i32 * ydir;
-            }
-            if xdiff != 0 {
-                x += ((i * xdiff) / r) as i32 * xdir;
+    /// Draws the `Canvas` turned 90° clockwise to a `String` and returns it, like
+    /// `rows_rotated`.
+    pub fn frame_rotated(&self) -> String {
+        self.rows_rotated().join("\n")
+    }
+
+    /// Returns the escape sequences needed to turn `previous`, as printed at the top-left corner
+    /// of the terminal, into this `Canvas`.
+    ///
+    /// Only the characters that differ are written, each run of them preceded by a cursor
+    /// movement, so this is much cheaper to send than a whole new frame.
+    pub fn frame_diff(&self, previous: &Canvas) -> String {
+        let mut positions = Vec::new();
+        for canvas in &[self, previous] {
+            for &(x, y) in canvas.chars.keys() {
+                positions.push((y, x));
+                if canvas.is_wide(x, y) {
+                    positions.push((y, x + 1));
+                }
             }
+        }
+        positions.sort_unstable();
+        positions.dedup();
 
-            self.set(x as u32, y as u32);
+        let colorize = self.colorize();
+        let mut result = String::new();
+        let mut cursor = None;
+        let (mut old, mut new) = (String::new(), String::new());
+        for (y, x) in positions {
+            old.clear();
+            new.clear();
+            previous.push_cell(&mut old, x, y, colorize);
+            self.push_cell(&mut new, x, y, colorize);
+            if old == new || new.is_empty() {
+                continue;
+            }
+            if cursor != Some((y, x)) {
+                result.push_str(&format!("\x1b[{};{}H", y as u32 + 1, x as u32 + 1));
+            }
+            result.push_str(&new);
+            cursor = Some((y, x + if self.is_wide(x, y) { 2 } else { 1 }));
         }
+        result
     }
 
-    /// Draws a line from `(x1, y1)` to `(x2, y2)` onto the `Canvas`
-    /// specifying the color of the line
-    pub fn line_colored(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: PixelColor) {
-        let xdiff = cmp::max(x1, x2) - cmp::min(x1, x2);
-        let ydiff = cmp::max(y1, y2) - cmp::min(y1, y2);
-        let xdir = if x1 <= x2 { 1 } else { -1 };
-        let ydir = if y1 <= y2 { 1 } else { -1 };
-
-        let r = cmp::max(xdiff, ydiff);
+    /// Detects whether two canvases have the same pixels set, ignoring colours, letters and
+    /// dimensions.
+    pub fn eq_pixels(&self, other: &Canvas) -> bool {
+        self.same_cells(other, |&(dots, _, _, _)| dots)
+    }
 
-        for i in 0..=r {
-            let mut x = x1 as i32;
-            let mut y = y1 as i32;
+    /// Detects whether two canvases have the same pixels set and letters drawn, ignoring colours
+    /// and dimensions.
+    pub fn eq_cells(&self, other: &Canvas) -> bool {
+        self.same_cells(other, |&(dots, c, _, _)| {
+            (dots, if dots == 0 { c } else { ' ' })
+        })
+    }
 
-            if ydiff != 0 {
-                y += ((i * ydiff) / r) as i32 * ydir;
+    /// Returns a 64-bit hash of the `Canvas`’s pixels, characters and colours.
+    ///
+    /// The hash doesn’t depend on the order in which things were drawn, so it can be used to
+    /// cheaply detect whether a frame has changed since it was last printed.
+    pub fn content_hash(&self) -> u64 {
+        let (mut maxrow, mut maxcol) = (self.width, self.height);
+        let mut sum = 0u64;
+        for (&(x, y), &(dots, c, colored, color)) in &self.chars {
+            maxrow = cmp::max(maxrow, x);
+            maxcol = cmp::max(maxcol, y);
+            if dots == 0 && c == ' ' {
+                continue;
             }
-            if xdiff != 0 {
-                x += ((i * xdiff) / r) as i32 * xdir;
+            let mut hasher = FnvHasher::default();
+            (x, y, dots).hash(&mut hasher);
+            if dots == 0 {
+                c.hash(&mut hasher);
+            } else if colored {
+                color_key(color).hash(&mut hasher);
             }
-
-            self.set_colored(x as u32, y as u32, color);
+            sum = sum.wrapping_add(hasher.finish());
         }
+        let mut hasher = FnvHasher::default();
+        (maxrow, maxcol, sum).hash(&mut hasher);
+        hasher.finish()
     }
-}
 
-/// A ‘turtle’ that can walk around a canvas drawing lines.
-pub struct Turtle {
-    pub x: f32,
-    pub y: f32,
-    pub brush: bool,
-    pub use_color: bool,
-    pub brush_color: PixelColor,
-    pub rotation: f32,
-    pub cvs: Canvas,
-}
+    /// Detects whether any pixel set in `other` is also set on the `Canvas` when `other` is
+    /// moved `dx` pixels right and `dy` pixels down.
+    ///
+    /// Letters are ignored. Whole characters are compared at once, so this is much faster than
+    /// checking the pixels one by one.
+    pub fn overlaps(&self, other: &Canvas, dx: i32, dy: i32) -> bool {
+        other
+            .shifted_dots(dx, dy)
+            .iter()
+            .any(|(pos, &dots)| self.chars.get(pos).is_some_and(|a| a.0 & dots != 0))
+    }
 
-impl Turtle {
-    /// Create a new `Turtle`, starting at the given coordinates.
+    /// Returns the coordinates, on the `Canvas`, of every pixel that is set both on the
+    /// `Canvas` and in `other` moved `dx` pixels right and `dy` pixels down, like `overlaps`.
     ///
-    /// The turtle starts with its brush down, facing right.
-    pub fn new(x: f32, y: f32) -> Turtle {
-        Turtle {
-            cvs: Canvas::new(0, 0),
-            x: x,
-            y: y,
-            brush: true,
-            use_color: false,
-            brush_color: PixelColor::White,
-            rotation: 0.0,
+    /// The points are ordered from top to bottom and then from left to right.
+    pub fn overlapping_points(&self, other: &Canvas, dx: i32, dy: i32) -> Vec<(u32, u32)> {
+        let mut points = Vec::new();
+        for (&(col, row), &dots) in &other.shifted_dots(dx, dy) {
+            let both = self.chars.get(&(col, row)).map_or(0, |a| a.0 & dots);
+            for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                for (x, &bit) in bits.iter().enumerate() {
+                    if both & bit != 0 {
+                        points.push((col as u32 * 2 + x as u32, row as u32 * 4 + y as u32));
+                    }
+                }
+            }
         }
+        points.sort_by_key(|&(x, y)| (y, x));
+        points
     }
 
-    /// Creates a new `Turtle` with the provided `Canvas`, starting at the given coordinates.
+    /// Returns a frame part of the way through a transition from `from` to `to`, where `t` is
+    /// between `0.0` (all `from`) and `1.0` (all `to`).
     ///
-    /// The turtle starts with its brush down, facing right.
-    pub fn from_canvas(x: f32, y: f32, cvs: Canvas) -> Turtle {
-        Turtle {
-            cvs: cvs,
-            x: x,
-            y: y,
-            brush: true,
-            use_color: false,
-            brush_color: PixelColor::White,
-            rotation: 0.0,
+    /// Pixels in only one of the canvases dissolve in or out in a pseudo-random order, so
+    /// stepping `t` from `0.0` to `1.0` gives a smooth transition. Where both have a true
+    /// colour, the colour is blended between them; otherwise, and for letters, the result
+    /// switches from `from` to `to` halfway through. The drawing modes are taken from `from`.
+    pub fn interpolate(from: &Canvas, to: &Canvas, t: f32) -> Canvas {
+        let t = t.clamp(0.0, 1.0);
+        let mut canvas = from.clone();
+        canvas.chars.clear();
+        canvas.indexed.clear();
+        canvas.log = None;
+        canvas.width = if t < 0.5 { from.width } else { to.width };
+        canvas.height = if t < 0.5 { from.height } else { to.height };
+
+        let empty = (0, ' ', false, PixelColor::White);
+        let mut keys: Vec<_> = from.chars.keys().chain(to.chars.keys()).cloned().collect();
+        keys.sort();
+        keys.dedup();
+        for (col, row) in keys {
+            let a = from.chars.get(&(col, row)).cloned().unwrap_or(empty);
+            let b = to.chars.get(&(col, row)).cloned().unwrap_or(empty);
+            let mut dots = 0;
+            for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                for (x, &bit) in bits.iter().enumerate() {
+                    let (in_a, in_b) = (a.0 & bit != 0, b.0 & bit != 0);
+                    let n = noise(col as u32 * 2 + x as u32, row as u32 * 4 + y as u32);
+                    let set = match (in_a, in_b) {
+                        (true, true) => true,
+                        (true, false) => n >= t,
+                        (false, true) => n < t,
+                        (false, false) => false,
+                    };
+                    if set {
+                        dots |= bit;
+                    }
+                }
+            }
+            let cell = match (a, b) {
+                ((_, _, true, ca), (_, _, true, cb)) if dots != 0 => {
+                    (dots, ' ', true, blend(cb, ca, t))
+                }
+                _ if dots != 0 => {
+                    let (colored, color) = match (a.0 != 0, b.0 != 0, t < 0.5) {
+                        (true, false, _) | (true, true, true) => (a.2, a.3),
+                        _ => (b.2, b.3),
+                    };
+                    (dots, ' ', colored, color)
+                }
+                _ if t < 0.5 => (0, a.1, false, PixelColor::White),
+                _ => (0, b.1, false, PixelColor::White),
+            };
+            if cell != empty {
+                canvas.chars.insert((col, row), cell);
+            }
         }
+        canvas
     }
 
-    /// Sets the width of a `Turtle`’s `Canvas`, and return it for use again.
-    pub fn width(mut self, width: u32) -> Turtle {
-        self.cvs.width = width as u16;
-        self
+    /// Returns a copy of the `Canvas` shrunk by `factor` in each direction, for drawing at a
+    /// higher resolution than the terminal can show.
+    ///
+    /// Each pixel of the result stands for a square of `factor` by `factor` pixels of the
+    /// `Canvas`, and is set if at least `coverage` (between `0.0` and `1.0`) of them are set,
+    /// which smooths out curves and diagonal lines. It takes the colour of the first coloured
+    /// character it covers. Letters are copied to the character they shrink into.
+    pub fn downsample(&self, factor: u32, coverage: f32) -> Canvas {
+        self.shrink(cmp::max(factor, 1) as f64, coverage)
     }
 
-    /// Sets the height of a `Turtle`’s `Canvas`, and return it for use again.
-    pub fn height(mut self, height: u32) -> Turtle {
-        self.cvs.height = height as u16;
-        self
+    /// Returns a copy of the `Canvas` shrunk to fit within `max_width` by `max_height` pixels,
+    /// keeping its proportions, such as for a preview of a large drawing.
+    ///
+    /// The whole drawing is fitted, including anything drawn outside the given dimensions of the
+    /// `Canvas`. Pixels are merged as in `downsample`, so a `coverage` of `0.0` keeps every
+    /// line visible while `0.5` only sets pixels where most of what they stand for is set.
+    /// Drawings that already fit are returned as they are.
+    pub fn fit_to(&self, max_width: u32, max_height: u32, coverage: f32) -> Canvas {
+        let (cols, rows) = self.cell_bounds();
+        let scale = f64::max(
+            cols as f64 * 2.0 / cmp::max(max_width, 1) as f64,
+            rows as f64 * 4.0 / cmp::max(max_height, 1) as f64,
+        );
+        if scale <= 1.0 {
+            return self.clone();
+        }
+        self.shrink(scale, coverage)
     }
 
-    /// Lifts the `Turtle`’s brush.
-    pub fn up(&mut self) {
-        self.brush = false;
-    }
+    /// Shrinks the `Canvas` by `scale` (at least `1.0`) in each direction, for `downsample`
+    /// and `fit_to`.
+    fn shrink(&self, scale: f64, coverage: f32) -> Canvas {
+        let mut canvas = self.clone();
+        canvas.chars.clear();
+        canvas.indexed.clear();
+        canvas.log = None;
+        canvas.width = ((self.width as f64 * 2.0 / scale).ceil() / 2.0).ceil() as u16;
+        canvas.height = ((self.height as f64 * 4.0 / scale).ceil() / 4.0).ceil() as u16;
 
-    /// Puts down the `Turtle`’s brush.
-    pub fn down(&mut self) {
-        self.brush = true;
+        let shrunk = |p: u32| (p as f64 / scale).floor() as u32;
+        let mut counts: FnvHashMap<(u32, u32), (u32, Option<PixelColor>)> = FnvHashMap::default();
+        let mut keys: Vec<_> = self.chars.keys().cloned().collect();
+        keys.sort_by_key(|&(col, row)| (row, col));
+        for (col, row) in keys {
+            let (dots, c, colored, color) = self.chars[&(col, row)];
+            if dots == 0 {
+                if c != ' ' {
+                    let (x, y) = (shrunk(col as u32 * 2), shrunk(row as u32 * 4));
+                    canvas.put_char(x / 2 * 2, y / 4 * 4, c);
+                }
+                continue;
+            }
+            for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                for (x, &bit) in bits.iter().enumerate() {
+                    if dots & bit == 0 {
+                        continue;
+                    }
+                    let px = shrunk(col as u32 * 2 + x as u32);
+                    let py = shrunk(row as u32 * 4 + y as u32);
+                    let count = counts.entry((px, py)).or_insert((0, None));
+                    count.0 += 1;
+                    if colored && count.1.is_none() {
+                        count.1 = Some(color);
+                    }
+                }
+            }
+        }
+
+        // The number of pixels shrunk into the pixel at `p` along one direction.
+        let span = |p: u32| ((p + 1) as f64 * scale).ceil() - (p as f64 * scale).ceil();
+        let coverage = coverage.clamp(0.0, 1.0);
+        for (&(x, y), &(count, color)) in &counts {
+            let needed = cmp::max(1, (coverage * (span(x) * span(y)) as f32).ceil() as u32);
+            if count >= needed {
+                canvas.paint_dot(x, y, color);
+            }
+        }
+        canvas
     }
 
-    /// Toggles the `Turtle`’s brush.
-    pub fn toggle(&mut self) {
-        self.brush = !self.brush;
+    /// Calls `f` with the coordinates, state and colour of every pixel within the bounds of the
+    /// `Canvas`, and sets or deletes each pixel (with the colour) that `f` returns.
+    ///
+    /// This allows effects such as thresholding, recolouring and masking to be applied in a
+    /// single pass. Since a Braille character can only have one colour, each character takes
+    /// the last colour returned for one of its set pixels. Letters are kept unless a pixel is
+    /// set where they are.
+    pub fn map_pixels<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32, u32, bool, Option<PixelColor>) -> (bool, Option<PixelColor>),
+    {
+        let (cols, rows) = self.cell_bounds();
+        for row in 0..rows {
+            for col in 0..cols {
+                let cell = self.chars.get(&(col, row)).cloned();
+                let (dots, c, colored, color) = cell.unwrap_or((0, ' ', false, PixelColor::White));
+                let old = if colored { Some(color) } else { None };
+                let (mut new_dots, mut new_color) = (0, None);
+                for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                    for (x, &bit) in bits.iter().enumerate() {
+                        let set = dots & bit != 0;
+                        let (px, py) = (col as u32 * 2 + x as u32, row as u32 * 4 + y as u32);
+                        if let (true, color) = f(px, py, set, if set { old } else { None }) {
+                            new_dots |= bit;
+                            new_color = color.or(new_color);
+                        }
+                    }
+                }
+                if new_dots != 0 {
+                    let colored = new_color.is_some();
+                    let color = new_color.unwrap_or(PixelColor::White);
+                    self.chars
+                        .insert((col, row), (new_dots, ' ', colored, color));
+                    self.indexed.remove(&(col, row));
+                } else if cell.is_some() {
+                    self.chars
+                        .insert((col, row), (0, c, false, PixelColor::White));
+                    self.indexed.remove(&(col, row));
+                }
+            }
+        }
     }
 
-    /// Use specific color the the brush.
-    pub fn color(&mut self, brush_color: PixelColor) {
-        self.use_color = true;
-        self.brush_color = brush_color;
+    /// Deletes every set pixel for which `f`, called with its coordinates and colour, returns
+    /// `false`.
+    ///
+    /// Characters left with no pixels are removed altogether, while letters are kept.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32, u32, Option<PixelColor>) -> bool,
+    {
+        let indexed = &mut self.indexed;
+        self.chars.retain(|&(col, row), a| {
+            if a.0 == 0 {
+                return a.1 != ' ';
+            }
+            let color = if a.2 { Some(a.3) } else { None };
+            for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                for (x, &bit) in bits.iter().enumerate() {
+                    let (px, py) = (col as u32 * 2 + x as u32, row as u32 * 4 + y as u32);
+                    if a.0 & bit != 0 && !f(px, py, color) {
+                        a.0 &= !bit;
+                    }
+                }
+            }
+            if a.0 == 0 {
+                indexed.remove(&(col, row));
+            }
+            a.0 != 0
+        });
     }
 
-    /// Remove color from brush.
-    pub fn clean_brush(&mut self) {
-        self.use_color = false;
+    /// Draws a line from `(x1, y1)` to `(x2, y2)` onto the `Canvas`.
+    pub fn line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        self.record(Command::Line { x1, y1, x2, y2 });
+        self.draw_line(x1, y1, x2, y2, None);
     }
 
-    /// Moves the `Turtle` forward by `dist` steps.
-    pub fn forward(&mut self, dist: f32) {
-        let x = self.x + degrees_to_radians(self.rotation).cos() * dist;
-        let y = self.y + degrees_to_radians(self.rotation).sin() * dist;
-        self.teleport(x, y);
+    /// Draws a line from `(x1, y1)` to `(x2, y2)` onto the `Canvas`
+    /// specifying the color of the line
+    pub fn line_colored(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: PixelColor) {
+        self.record(Command::LineColored {
+            x1,
+            y1,
+            x2,
+            y2,
+            color,
+        });
+        self.draw_line(x1, y1, x2, y2, Some(color));
     }
 
-    /// Moves the `Turtle` backward by `dist` steps.
-    pub fn back(&mut self, dist: f32) {
-        self.forward(-dist);
+    /// Draws a line from `(x1, y1)` to `(x2, y2)` onto the `Canvas` in the colour at `index`
+    /// of its palette, which is remembered like in `set_indexed`.
+    pub fn line_indexed(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, index: usize) {
+        let color = self.palette.get(index);
+        self.indexing = Some(index);
+        self.line_colored(x1, y1, x2, y2, color);
+        self.indexing = None;
     }
 
-    /// Teleports the `Turtle` to the given coordinates.
+    /// Draws a line for each of the given pairs of end points, like calling `line` for each.
     ///
-    /// Note that this draws a line between the old position and the new one if the `Turtle`’s
-    /// brush is down.
-    pub fn teleport(&mut self, x: f32, y: f32) {
-        if self.brush {
-            if self.use_color {
-                self.cvs.line_colored(
-                    cmp::max(0, self.x.round() as i32) as u32,
-                    cmp::max(0, self.y.round() as i32) as u32,
-                    cmp::max(0, x.round() as i32) as u32,
-                    cmp::max(0, y.round() as i32) as u32,
-                    self.brush_color,
-                );
-            } else {
-                self.cvs.line(
-                    cmp::max(0, self.x.round() as i32) as u32,
-                    cmp::max(0, self.y.round() as i32) as u32,
-                    cmp::max(0, x.round() as i32) as u32,
-                    cmp::max(0, y.round() as i32) as u32,
-                );
-            }
+    /// The pixels of all the lines are gathered up first and each character is written once,
+    /// which is much faster than drawing the lines one by one when there are many of them.
+    pub fn lines(&mut self, segments: &[Segment]) {
+        for &((x1, y1), (x2, y2)) in segments {
+            self.record(Command::Line { x1, y1, x2, y2 });
         }
+        self.draw_lines(segments, None);
+    }
 
-        self.x = x;
-        self.y = y;
+    /// Draws a line in the given colour for each of the given pairs of end points, like `lines`.
+    pub fn lines_colored(&mut self, segments: &[Segment], color: PixelColor) {
+        for &((x1, y1), (x2, y2)) in segments {
+            self.record(Command::LineColored {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            });
+        }
+        self.draw_lines(segments, Some(color));
     }
 
-    /// Turns the `Turtle` right (clockwise) by `angle` degrees.
-    pub fn right(&mut self, angle: f32) {
-        self.rotation += angle;
+    /// Draws an ellipse centred on `(xm, ym)`, with a horizontal radius of `a` pixels and a
+    /// vertical radius of `b` pixels.
+    ///
+    /// The vertical radius is measured in horizontal pixels, according to the pixel aspect, so
+    /// equal radii always look like a circle. Parts of the ellipse that would land at negative
+    /// coordinates are skipped.
+    pub fn ellipse_center(&mut self, xm: u32, ym: u32, a: u32, b: u32) {
+        self.record(Command::Ellipse { xm, ym, a, b });
+        self.draw_ellipse(xm, ym, a, b, None);
     }
 
-    /// Turns the `Turtle` left (clockwise) by `angle` degrees.
-    pub fn left(&mut self, angle: f32) {
-        self.rotation -= angle;
+    /// Draws an ellipse like `ellipse_center`, in the given colour.
+    pub fn ellipse_center_colored(&mut self, xm: u32, ym: u32, a: u32, b: u32, color: PixelColor) {
+        self.record(Command::EllipseColored {
+            xm,
+            ym,
+            a,
+            b,
+            color,
+        });
+        self.draw_ellipse(xm, ym, a, b, Some(color));
     }
 
-    /// Writes the `Turtle`’s `Canvas` to a `String` and returns it.
-    pub fn frame(&self) -> String {
-        self.cvs.frame()
+    /// Draws part of the ellipse centred on `(xm, ym)` with radii `a` and `b` (as in
+    /// `ellipse_center`), from `start_deg` to `end_deg` degrees.
+    ///
+    /// Angles are measured clockwise from the right of the centre, like a `Turtle`’s rotation,
+    /// and the arc is drawn clockwise from its start to its end. Parts of the arc that would
+    /// land at negative coordinates are skipped.
+    pub fn ellipse_arc(&mut self, xm: u32, ym: u32, a: u32, b: u32, start_deg: f32, end_deg: f32) {
+        self.record(Command::Arc {
+            xm,
+            ym,
+            a,
+            b,
+            start_deg,
+            end_deg,
+        });
+        let b = self.vertical(b);
+        let segments = raster::arc_segments(xm, ym, a, b, start_deg, end_deg, self.tolerance);
+        for ((x1, y1), (x2, y2)) in segments {
+            self.draw_line(x1, y1, x2, y2, None);
+        }
     }
-}
 
-fn degrees_to_radians(deg: f32) -> f32 {
+    /// Draws the contents of `other` onto the `Canvas`, with its top-left corner at `(x, y)`.
+    ///
+    /// Pixels set in `other` are set on the `Canvas` (taking on their colour), letters are
+    /// copied over, and everything else is left untouched.
+    pub fn blit(&mut self, other: &Canvas, x: u32, y: u32) {
+        self.blit_with_opacity(other, x, y, 1.0);
+    }
+
+    /// Draws the contents of `other` onto the `Canvas` like `blit`, blending its true colours
+    /// with the ones already on the `Canvas` according to `opacity` (between `0.0` and `1.0`).
+    ///
+    /// Pixels are blended with black where the `Canvas` has no true colour of its own. Since a
+    /// Braille character can only have one colour, blending happens per character rather than
+    /// per pixel. Named colours can’t be blended and are copied as they are.
+    pub fn blit_with_opacity(&mut self, other: &Canvas, x: u32, y: u32, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let aligned = x.is_multiple_of(2) && y.is_multiple_of(4);
+        for (&(col, row), &(dots, c, colored, color)) in &other.chars {
+            let (px, py) = (col as u32 * 2 + x, row as u32 * 4 + y);
+            if dots == 0 {
+                if c != ' ' {
+                    self.put_char(px, py, c);
+                }
+            } else if aligned {
+                let pos = ((px / 2) as u16, (py / 4) as u16);
+                self.merge_dots(pos, dots, colored, color, opacity);
+            } else {
+                for (dy, bits) in PIXEL_MAP.iter().enumerate() {
+                    for (dx, &bit) in bits.iter().enumerate() {
+                        if dots & bit == 0 {
+                            continue;
+                        }
+                        let (px, py) = (px + dx as u32, py + dy as u32);
+                        let pos = ((px / 2) as u16, (py / 4) as u16);
+                        let bit = PIXEL_MAP[py as usize % 4][px as usize % 2];
+                        self.merge_dots(pos, bit, colored, color, opacity);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deletes the pixels along a line from `(x1, y1)` to `(x2, y2)`.
+    pub fn unset_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        self.record(Command::UnsetLine { x1, y1, x2, y2 });
+        for (x, y) in line_points(x1, y1, x2, y2) {
+            let (x, y) = self.wrap(x, y);
+            let (row, col) = ((x / 2) as u16, (y / 4) as u16);
+            if let Some(a) = self.chars.get_mut(&(row, col)) {
+                a.0 &= !PIXEL_MAP[y as usize % 4][x as usize % 2];
+            }
+        }
+    }
+
+    /// Fills the region of `width` by `height` pixels whose top-left corner is at `(x, y)` by
+    /// repeating `pattern` across it.
+    ///
+    /// The pattern’s top-left pixel is aligned with the top-left of the region, and its size is
+    /// taken to be its own width and height (or the extent of its contents, if larger). Unset
+    /// pixels in the pattern leave the `Canvas` untouched.
+    pub fn tile(&mut self, pattern: &Canvas, x: u32, y: u32, width: u32, height: u32) {
+        let (cols, rows) = pattern.cell_bounds();
+        let (pw, ph) = (cols as u32 * 2, rows as u32 * 4);
+        if pw == 0 || ph == 0 {
+            return;
+        }
+        for j in 0..height {
+            for i in 0..width {
+                let (px, py) = (i % pw, j % ph);
+                if !pattern.get(px, py) {
+                    continue;
+                }
+                match pattern.chars.get(&((px / 2) as u16, (py / 4) as u16)) {
+                    Some(&(_, _, true, color)) => self.set_colored(x + i, y + j, color),
+                    _ => self.set(x + i, y + j),
+                }
+            }
+        }
+    }
+
+    /// Fills the polygon with the given corners, including its outline.
+    ///
+    /// The polygon is closed automatically, and may cross itself; where it does, areas enclosed
+    /// an odd number of times are filled.
+    pub fn fill_polygon(&mut self, points: &[(u32, u32)]) {
+        self.record(Command::FillPolygon {
+            points: points.to_vec(),
+        });
+        self.draw_polygon(points, None);
+    }
+
+    /// Fills the polygon with the given corners in the given colour, like `fill_polygon`.
+    pub fn fill_polygon_colored(&mut self, points: &[(u32, u32)], color: PixelColor) {
+        self.record(Command::FillPolygonColored {
+            points: points.to_vec(),
+            color,
+        });
+        self.draw_polygon(points, Some(color));
+    }
+
+    /// Sets a pseudo-random fraction `density` (between `0.0` and `1.0`) of the pixels in the
+    /// region of `width` by `height` pixels whose top-left corner is at `(x, y)`.
+    ///
+    /// The chosen pixels are spread evenly across the region, so the density is perceived as a
+    /// shade of grey. The same pixels are chosen every time, so redrawing a region with the
+    /// same density doesn’t make it flicker.
+    pub fn fill_rect_density(&mut self, x: u32, y: u32, width: u32, height: u32, density: f32) {
+        self.record(Command::FillRectDensity {
+            x,
+            y,
+            width,
+            height,
+            density,
+        });
+        for (px, py) in rect_density_points(x, y, width, height, density) {
+            self.paint(px, py, None);
+        }
+    }
+
+    /// Sets a pseudo-random fraction `density` of the pixels inside the ellipse centred on
+    /// `(xm, ym)` with radii `a` and `b` (as in `ellipse_center`), like `fill_rect_density`.
+    pub fn fill_ellipse_density(&mut self, xm: u32, ym: u32, a: u32, b: u32, density: f32) {
+        self.record(Command::FillEllipseDensity {
+            xm,
+            ym,
+            a,
+            b,
+            density,
+        });
+        let b = self.vertical(b);
+        for (px, py) in ellipse_density_points(xm, ym, a, b, density) {
+            self.paint(px, py, None);
+        }
+    }
+
+    /// Sets the axes across which drawing is mirrored.
+    ///
+    /// Everything that sets pixels from coordinates is mirrored: `set`, `set_colored` and
+    /// `set_indexed`, lines, ellipses and arcs, polygons, density fills, `stamp` and the pixels
+    /// that `set_rows_from_bits` sets. Erasing is not: `unset`, `toggle`, `unset_line` and the
+    /// pixels that `set_rows_from_bits` clears only change the pixels given, as do letters and
+    /// pixels copied from other canvases with `blit` or `tile`. Mirrored pixels that would land
+    /// at negative coordinates are skipped.
+    pub fn set_symmetry(&mut self, symmetry: Symmetry) {
+        self.symmetry = symmetry;
+    }
+
+    /// Returns the axes across which drawing is currently mirrored.
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    /// Sets how far, in pixels, the straight segments that curves are drawn with may stray from
+    /// the true curve.
+    ///
+    /// Smaller tolerances give smoother curves on large canvases, while larger ones save time
+    /// drawing segments that are too short to see. The default is `0.5`.
+    pub fn set_curve_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Returns how far the segments of curves may stray from the true curve.
+    pub fn curve_tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Sets whether coordinates wrap around the edges of the `Canvas`, as if it were a torus.
+    ///
+    /// While wrapping, pixels drawn past the right or bottom edge of the `Canvas` (as given to
+    /// `new`) land that far from the left or top edge instead, so lines crossing an edge carry
+    /// on from the opposite one. A `Turtle` walking past any edge comes back on the opposite
+    /// side. Edges of a `Canvas` with no width or height don’t wrap.
+    pub fn set_wrapping(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Returns the size of the `Canvas` in pixels if coordinates wrap around its edges.
+    pub fn wrapping(&self) -> Option<(u32, u32)> {
+        if self.wrap {
+            Some((self.width as u32 * 2, self.height as u32 * 4))
+        } else {
+            None
+        }
+    }
+
+    /// Sets the width of each pixel divided by its height, as shown by the terminal.
+    ///
+    /// Ellipses and a `Turtle`’s movements take this into account so that shapes keep their
+    /// proportions on screen, whatever the shape of the terminal’s characters. For example, if
+    /// a terminal’s characters are more than twice as tall as they are wide, its pixels are
+    /// taller than they are wide and the aspect is less than `1.0`. The default is `1.0`.
+    pub fn set_pixel_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+    }
+
+    /// Returns the width of each pixel divided by its height.
+    pub fn pixel_aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    /// Makes lines cycle through the hues of the rainbow as they are drawn, advancing by `step`
+    /// degrees per pixel of distance covered.
+    ///
+    /// The hue carries on from where the previous line left off, and overrides the colour given
+    /// to `line_colored`.
+    pub fn rainbow(&mut self, step: f32) {
+        let hue = self.rainbow.map_or(0.0, |(hue, _)| hue);
+        self.rainbow = Some((hue, step));
+    }
+
+    /// Stops lines from cycling through the hues of the rainbow.
+    pub fn no_rainbow(&mut self) {
+        self.rainbow = None;
+    }
+
+    /// Sets whether colours are emitted when the `Canvas` is drawn.
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    /// Sets the range of colours the terminal can show, which true colours are brought down to
+    /// when the `Canvas` is drawn.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+    }
+
+    /// Chooses the colour mode and depth that suit the terminal standard output is connected to,
+    /// as found by `detect_color_depth`.
+    pub fn detect_colors(&mut self) {
+        match detect_color_depth() {
+            Some(depth) => {
+                self.color_mode = ColorMode::Always;
+                self.color_depth = depth;
+            }
+            None => self.color_mode = ColorMode::Never,
+        }
+    }
+
+    /// Sets when the colour is reset while the `Canvas` is drawn.
+    pub fn set_color_reset(&mut self, reset: ColorReset) {
+        self.color_reset = reset;
+    }
+
+    /// Sets a theme that remaps each pixel colour when the `Canvas` is drawn, such as
+    /// `light_theme` for terminals with a light background.
+    pub fn set_theme(&mut self, theme: fn(PixelColor) -> PixelColor) {
+        self.theme = Some(theme);
+    }
+
+    /// Removes the theme, drawing pixel colours as they were set.
+    pub fn clear_theme(&mut self) {
+        self.theme = None;
+    }
+
+    /// Sets the function that turns the dots set in a character cell into the character drawn
+    /// for it, in place of the Braille patterns.
+    ///
+    /// The dots are given as a bitmask laid out like `PIXEL_MAP`, and are never all unset.
+    /// This allows for other character sets, such as `ascii_glyph` for terminals without
+    /// Braille in their fonts, or the private-use glyphs of a custom font. `braille` restores
+    /// the usual characters.
+    pub fn set_glyphs(&mut self, glyphs: fn(u8) -> char) {
+        self.glyphs = glyphs;
+    }
+
+    /// Sets the palette that `set_indexed` and `line_indexed` take their colours from.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Rotates the palette by one place, so that everything drawn with `set_indexed` or
+    /// `line_indexed` takes on the colour of the next index along.
+    ///
+    /// Calling this between frames animates the colours without drawing anything again, for
+    /// effects such as flowing water or marching lines.
+    pub fn cycle_palette(&mut self) {
+        self.cycle = self.cycle.wrapping_add(1);
+    }
+
+    /// Returns the palette that `set_indexed` and `line_indexed` take their colours from, which
+    /// is the default `Palette` unless another has been set.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Sets the `Style` used to colour characters when the `Canvas` is drawn.
+    pub fn set_style<S: Style + 'static>(&mut self, style: S) {
+        self.style = Arc::new(style);
+    }
+
+    /// Starts recording drawing commands issued to the `Canvas`, discarding any previously
+    /// recorded ones.
+    ///
+    /// The recorded commands can be retrieved with `commands` or `stop_recording`, and replayed
+    /// onto another `Canvas` with `replay`. Not every call is recorded; see `Command` for the
+    /// ones that are left out.
+    pub fn start_recording(&mut self) {
+        self.log = Some(Vec::new());
+    }
+
+    /// Stops recording drawing commands and returns the ones recorded so far.
+    pub fn stop_recording(&mut self) -> Vec<Command> {
+        self.log.take().unwrap_or_default()
+    }
+
+    /// Returns the drawing commands recorded so far, or an empty slice if the `Canvas` is not
+    /// recording.
+    pub fn commands(&self) -> &[Command] {
+        self.log.as_ref().map_or(&[], |log| &log[..])
+    }
+
+    /// Returns an estimate of the number of bytes of memory used by the `Canvas`, counting its
+    /// cells and any recorded commands.
+    ///
+    /// Drawing far outside a `Canvas` makes it grow, so this helps to spot canvases that have
+    /// become larger than expected and need clearing or trimming with `retain`.
+    pub fn memory_footprint(&self) -> usize {
+        let commands = self.log.as_ref().map_or(0, |log| {
+            let text = log.iter().map(|command| match *command {
+                Command::Text { ref text, .. } => text.capacity(),
+                _ => 0,
+            });
+            log.capacity() * mem::size_of::<Command>() + text.sum::<usize>()
+        });
+        let indexed = self.indexed.capacity() * (mem::size_of::<((u16, u16), usize)>() + 1);
+        mem::size_of::<Canvas>() - mem::size_of::<Cells>()
+            + self.chars.memory_footprint()
+            + indexed
+            + commands
+    }
+
+    /// Replays a sequence of recorded drawing commands onto the `Canvas`, multiplying every
+    /// coordinate by `scale`.
+    pub fn replay(&mut self, commands: &[Command], scale: f32) {
+        for command in commands {
+            command.apply(self, scale);
+        }
+    }
+
+    /// Returns a copy of the `Canvas` turned 90° clockwise.
+    fn rotated(&self) -> Canvas {
+        let (_, maxcol) = self.render_bounds();
+        let bottom = maxcol as u32 * 4 + 3;
+        let mut canvas = self.clone();
+        canvas.chars.clear();
+        canvas.indexed.clear();
+        canvas.log = None;
+        canvas.width = self.height * 2;
+        canvas.height = self.width / 2;
+        for (&(col, row), &(dots, c, colored, color)) in &self.chars {
+            let (px, py) = (col as u32 * 2, row as u32 * 4);
+            if dots == 0 {
+                if c != ' ' {
+                    canvas.put_char(bottom - py - 3, px, c);
+                }
+                continue;
+            }
+            for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                for (x, &bit) in bits.iter().enumerate() {
+                    if dots & bit != 0 {
+                        let color = if colored { Some(color) } else { None };
+                        canvas.paint_dot(bottom - (py + y as u32), px + x as u32, color);
+                    }
+                }
+            }
+        }
+        canvas
+    }
+
+    /// Wraps the given coordinates around the edges of the `Canvas` if it is wrapping.
+    fn wrap(&self, x: u32, y: u32) -> (u32, u32) {
+        match self.wrapping() {
+            Some((w, h)) => (if w > 0 { x % w } else { x }, if h > 0 { y % h } else { y }),
+            None => (x, y),
+        }
+    }
+
+    /// Converts a vertical distance in horizontal pixels into vertical pixels.
+    fn vertical(&self, distance: u32) -> u32 {
+        (distance as f32 * self.aspect).round() as u32
+    }
+
+    /// Returns the pixels set on the `Canvas`, grouped into cells, after moving them `dx` pixels
+    /// right and `dy` pixels down. Pixels moved to negative coordinates are dropped.
+    fn shifted_dots(&self, dx: i32, dy: i32) -> FnvHashMap<(u16, u16), u8> {
+        let mut shifted = FnvHashMap::default();
+        let aligned = dx % 2 == 0 && dy % 4 == 0;
+        for (&(col, row), &(dots, _, _, _)) in &self.chars {
+            if dots == 0 {
+                continue;
+            }
+            let (px, py) = (col as i64 * 2 + dx as i64, row as i64 * 4 + dy as i64);
+            if aligned {
+                if px >= 0 && py >= 0 {
+                    *shifted
+                        .entry(((px / 2) as u16, (py / 4) as u16))
+                        .or_insert(0) |= dots;
+                }
+                continue;
+            }
+            for (y, bits) in PIXEL_MAP.iter().enumerate() {
+                for (x, &bit) in bits.iter().enumerate() {
+                    let (px, py) = (px + x as i64, py + y as i64);
+                    if dots & bit == 0 || px < 0 || py < 0 {
+                        continue;
+                    }
+                    let bit = PIXEL_MAP[py as usize % 4][px as usize % 2];
+                    *shifted
+                        .entry(((px / 2) as u16, (py / 4) as u16))
+                        .or_insert(0) |= bit;
+                }
+            }
+        }
+        shifted
+    }
+
+    fn same_cells<K, F>(&self, other: &Canvas, key: F) -> bool
+    where
+        K: PartialEq,
+        F: Fn(&(u8, char, bool, PixelColor)) -> K,
+    {
+        let empty = key(&(0, ' ', false, PixelColor::White));
+        let contained = |a: &Canvas, b: &Canvas| {
+            a.chars.iter().all(|(pos, cell)| {
+                let k = key(cell);
+                match b.chars.get(pos) {
+                    Some(other) => k == key(other),
+                    None => k == empty,
+                }
+            })
+        };
+        contained(self, other) && contained(other, self)
+    }
+
+    fn render_cells(&self, minrow: u16, mincol: u16, maxrow: u16, maxcol: u16) -> Vec<String> {
+        let colorize = self.colorize();
+        let carry = colorize && self.color_reset == ColorReset::End;
+        let mut current = None;
+        let mut result = Vec::with_capacity((maxcol as usize + 1).saturating_sub(mincol as usize));
+        for y in mincol..=maxcol {
+            let mut row =
+                String::with_capacity((maxrow as usize + 1).saturating_sub(minrow as usize));
+            let mut next = minrow as u32;
+            while next <= maxrow as u32 {
+                let x = next as u16;
+                next += 1;
+                if !self.chars.chunk_used((x, y)) && !self.covered(x, y) {
+                    // Nothing is drawn in the rest of the chunk, so it is left blank.
+                    let end = cmp::min(
+                        maxrow as u32,
+                        (x / cells::CHUNK + 1) as u32 * cells::CHUNK as u32 - 1,
+                    );
+                    row.extend((x as u32..=end).map(|_| ' '));
+                    next = end + 1;
+                    continue;
+                }
+                if !carry {
+                    self.push_cell(&mut row, x, y, colorize);
+                    continue;
+                }
+                match self.glyph(x, y, colorize) {
+                    // Blank spaces look the same in any colour, so they don’t switch it.
+                    Some((' ', _)) => row.push(' '),
+                    Some((c, color)) => {
+                        if color != current {
+                            match color {
+                                Some(color) => self.set_color(&mut row, color),
+                                None => self.style.reset(&mut row),
+                            }
+                            current = color;
+                        }
+                        row.push(c);
+                    }
+                    None => {}
+                }
+            }
+            result.push(row);
+        }
+        if current.is_some() {
+            if let Some(row) = result.last_mut() {
+                self.style.reset(row);
+            }
+        }
+        result
+    }
+
+    fn render_bounds(&self) -> (u16, u16) {
+        let (maxrow, maxcol) = self.chars.extent().unwrap_or((0, 0));
+        (cmp::max(maxrow, self.width), cmp::max(maxcol, self.height))
+    }
+
+    fn cell_bounds(&self) -> (u16, u16) {
+        match self.chars.extent() {
+            Some((x, y)) => (cmp::max(self.width, x + 1), cmp::max(self.height, y + 1)),
+            None => (self.width, self.height),
+        }
+    }
+
+    fn merge_dots(
+        &mut self,
+        pos: (u16, u16),
+        dots: u8,
+        colored: bool,
+        color: PixelColor,
+        opacity: f32,
+    ) {
+        let a = self.chars.entry(pos, (0, ' ', false, PixelColor::White));
+        let under = if a.0 != 0 && a.2 {
+            a.3
+        } else {
+            PixelColor::Black
+        };
+        a.0 |= dots;
+        a.1 = ' ';
+        if colored {
+            a.2 = true;
+            a.3 = blend(color, under, opacity);
+            self.indexed.remove(&pos);
+        }
+    }
+
+    fn colorize(&self) -> bool {
+        match self.color_mode {
+            ColorMode::Always => true,
+            ColorMode::Auto => colored::control::SHOULD_COLORIZE.should_colorize(),
+            ColorMode::Never => false,
+        }
+    }
+
+    fn is_wide(&self, x: u16, y: u16) -> bool {
+        match self.chars.get(&(x, y)) {
+            Some(&(0, c, _, _)) => char_width(c) == 2,
+            _ => false,
+        }
+    }
+
+    /// Detects whether the character at the given cell is hidden behind a wide letter.
+    fn covered(&self, x: u16, y: u16) -> bool {
+        let mut wide = 0;
+        while wide < x && self.is_wide(x - wide - 1, y) {
+            wide += 1;
+        }
+        wide % 2 == 1
+    }
+
+    fn push_cell(&self, out: &mut String, x: u16, y: u16, colorize: bool) {
+        match self.glyph(x, y, colorize) {
+            Some((c, Some(color))) => self.paint_char(out, c, color),
+            Some((c, None)) => out.push(c),
+            None => {}
+        }
+    }
+
+    /// Writes `c` coloured with `color`, as a colour from the 256-colour palette if that is
+    /// all the terminal can show.
+    fn paint_char(&self, out: &mut String, c: char, color: PixelColor) {
+        match (self.color_depth, color) {
+            (ColorDepth::Ansi256, PixelColor::TrueColor { r, g, b }) => {
+                self.style.paint_indexed(out, c, nearest_indexed(r, g, b))
+            }
+            _ => self.style.paint(out, c, color),
+        }
+    }
+
+    /// Switches the colour of the characters that follow, like `paint_char`.
+    fn set_color(&self, out: &mut String, color: PixelColor) {
+        match (self.color_depth, color) {
+            (ColorDepth::Ansi256, PixelColor::TrueColor { r, g, b }) => {
+                self.style.set_color_indexed(out, nearest_indexed(r, g, b))
+            }
+            _ => self.style.set_color(out, color),
+        }
+    }
+
+    /// Returns the colour that the cell at `pos`, coloured with `color`, is shown in, after
+    /// rotating the palette and applying the theme.
+    fn shown_color(&self, pos: (u16, u16), color: PixelColor) -> PixelColor {
+        let color = match self.indexed.get(&pos) {
+            // The cell may have been painted over in another colour since it was indexed.
+            Some(&index) if self.palette.get(index) == color => {
+                self.palette.get(index.wrapping_add(self.cycle))
+            }
+            _ => color,
+        };
+        self.theme.map_or(color, |theme| theme(color))
+    }
+
+    /// Returns the character drawn at the given cell and the colour to draw it in, or `None`
+    /// if the cell is hidden behind a wide letter.
+    fn glyph(&self, x: u16, y: u16, colorize: bool) -> Option<(char, Option<PixelColor>)> {
+        if self.covered(x, y) {
+            return None;
+        }
+        let cell = self.chars.get(&(x, y)).cloned();
+        Some(match cell.unwrap_or((0, ' ', false, PixelColor::White)) {
+            (0, c, _, _) => (c, None),
+            (dots, _, true, color) if colorize => {
+                let color = self.shown_color((x, y), color);
+                let color = match self.color_depth {
+                    ColorDepth::TrueColor | ColorDepth::Ansi256 => color,
+                    ColorDepth::Ansi16 => nearest_ansi(color),
+                };
+                ((self.glyphs)(dots), Some(color))
+            }
+            (dots, _, _, _) => ((self.glyphs)(dots), None),
+        })
+    }
+
+    fn record(&mut self, command: Command) {
+        if let Some(ref mut log) = self.log {
+            log.push(command);
+        }
+    }
+
+    fn paint(&mut self, x: u32, y: u32, color: Option<PixelColor>) {
+        let sym = self.symmetry;
+        if !sym.is_mirrored() {
+            let (x, y) = self.wrap(x, y);
+            self.paint_dot(x, y, color);
+            return;
+        }
+
+        let (cx, cy) = (sym.x as i64, sym.y as i64);
+        let mut points = [(0i64, 0i64); 8];
+        points[0] = (x as i64, y as i64);
+        let mut n = 1;
+        if sym.horizontal {
+            for i in 0..n {
+                points[n + i] = (points[i].0, 2 * cy - points[i].1);
+            }
+            n *= 2;
+        }
+        if sym.vertical {
+            for i in 0..n {
+                points[n + i] = (2 * cx - points[i].0, points[i].1);
+            }
+            n *= 2;
+        }
+        if sym.diagonal {
+            for i in 0..n {
+                points[n + i] = (cx + points[i].1 - cy, cy + points[i].0 - cx);
+            }
+            n *= 2;
+        }
+        for &(px, py) in &points[..n] {
+            if px >= 0 && py >= 0 && px <= u32::MAX as i64 && py <= u32::MAX as i64 {
+                let (px, py) = self.wrap(px as u32, py as u32);
+                self.paint_dot(px, py, color);
+            }
+        }
+    }
+
+    fn paint_dot(&mut self, x: u32, y: u32, color: Option<PixelColor>) {
+        let pos = ((x / 2) as u16, (y / 4) as u16);
+        self.paint_dots(pos, PIXEL_MAP[y as usize % 4][x as usize % 2], color);
+    }
+
+    /// Sets the given dots of the cell at `pos`, as `paint_dot` does for one pixel.
+    fn paint_dots(&mut self, pos: (u16, u16), dots: u8, color: Option<PixelColor>) {
+        let a = self.chars.entry(pos, (0, ' ', false, PixelColor::White));
+        a.0 |= dots;
+        a.1 = ' ';
+        a.2 = color.is_some();
+        a.3 = color.unwrap_or(PixelColor::White);
+        match self.indexing {
+            Some(index) => self.indexed.insert(pos, index),
+            None => self.indexed.remove(&pos),
+        };
+    }
+
+    fn put_char(&mut self, x: u32, y: u32, c: char) {
+        if char_width(c) == 0 {
+            return;
+        }
+        let (row, col) = ((x / 2) as u16, (y / 4) as u16);
+        let a = self
+            .chars
+            .entry((row, col), (0, ' ', false, PixelColor::White));
+        a.0 = 0;
+        a.1 = c;
+        a.2 = false;
+        a.3 = PixelColor::White;
+        self.indexed.remove(&(row, col));
+    }
+
+    fn draw_ellipse(&mut self, xm: u32, ym: u32, a: u32, b: u32, color: Option<PixelColor>) {
+        let b = self.vertical(b);
+        for (x, y) in ellipse_points(xm, ym, a, b) {
+            self.paint(x, y, color);
+        }
+    }
+
+    fn draw_polygon(&mut self, points: &[(u32, u32)], color: Option<PixelColor>) {
+        for (x, y) in raster::polygon_inside(points) {
+            self.paint(x, y, color);
+        }
+        for (i, &(x1, y1)) in points.iter().enumerate() {
+            let (x2, y2) = points[(i + 1) % points.len()];
+            self.draw_line(x1, y1, x2, y2, color);
+        }
+    }
+
+    fn draw_lines(&mut self, segments: &[Segment], color: Option<PixelColor>) {
+        let sym = self.symmetry;
+        if self.rainbow.is_some() || self.wrap || sym.is_mirrored() {
+            for &((x1, y1), (x2, y2)) in segments {
+                self.draw_line(x1, y1, x2, y2, color);
+            }
+            return;
+        }
+        let mut cells: FnvHashMap<(u16, u16), u8> = FnvHashMap::default();
+        for &((x1, y1), (x2, y2)) in segments {
+            for (x, y) in line_points(x1, y1, x2, y2) {
+                let bit = PIXEL_MAP[y as usize % 4][x as usize % 2];
+                *cells.entry(((x / 2) as u16, (y / 4) as u16)).or_insert(0) |= bit;
+            }
+        }
+        for (pos, dots) in cells {
+            self.paint_dots(pos, dots, color);
+        }
+    }
+
+    fn draw_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: Option<PixelColor>) {
+        let xdiff = cmp::max(x1, x2) - cmp::min(x1, x2);
+        let ydiff = cmp::max(y1, y2) - cmp::min(y1, y2);
+        let r = cmp::max(xdiff, ydiff);
+        let length = ((xdiff as f32).powi(2) + (ydiff as f32).powi(2)).sqrt();
+
+        for (i, (x, y)) in line_points(x1, y1, x2, y2).enumerate() {
+            let color = match self.rainbow {
+                Some((hue, step)) if r != 0 => {
+                    Some(hue_to_color(hue + step * length * i as f32 / r as f32))
+                }
+                Some((hue, _)) => Some(hue_to_color(hue)),
+                None => color,
+            };
+            self.paint(x, y, color);
+        }
+
+        if let Some((ref mut hue, step)) = self.rainbow {
+            *hue = (*hue + step * length) % 360.0;
+        }
+    }
+}
+
+impl PartialEq for Canvas {
+    fn eq(&self, other: &Canvas) -> bool {
+        self.chars == other.chars && self.width == other.width && self.height == other.height
+    }
+}
+
+impl Eq for Canvas {}
+
+impl Extend<(u32, u32)> for Canvas {
+    /// Sets a pixel at each of the given coordinates.
+    fn extend<I: IntoIterator<Item = (u32, u32)>>(&mut self, iter: I) {
+        for (x, y) in iter {
+            self.set(x, y);
+        }
+    }
+}
+
+impl FromIterator<(u32, u32)> for Canvas {
+    /// Creates a new `Canvas` with a pixel set at each of the given coordinates.
+    ///
+    /// The `Canvas` starts off with no width or height, and expands to fit the pixels.
+    fn from_iter<I: IntoIterator<Item = (u32, u32)>>(iter: I) -> Canvas {
+        let mut canvas = Canvas::new(0, 0);
+        canvas.extend(iter);
+        canvas
+    }
+}
+
+/// Whether a `Canvas` emits colours when it is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Always emit colours.
+    Always,
+    /// Emit colours unless the `NO_COLOR` environment variable is set or standard output is not
+    /// a terminal (as decided by `colored`, so `CLICOLOR` and `CLICOLOR_FORCE` are respected
+    /// too).
+    #[default]
+    Auto,
+    /// Never emit colours.
+    Never,
+}
+
+/// The range of colours a terminal can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// Any 24-bit true colour.
+    #[default]
+    TrueColor,
+    /// The 256-colour palette, so true colours are replaced by the closest colour in it.
+    Ansi256,
+    /// Only the 16 standard colours, so true colours are replaced by the closest of them.
+    Ansi16,
+}
+
+/// Works out the range of colours the terminal connected to standard output can show, or
+/// `None` if colours shouldn’t be used at all.
+///
+/// No colours are used if standard output isn’t a terminal, if `TERM` is `dumb`, or if
+/// `colored` decides against them (because of `NO_COLOR`, for instance). Otherwise, true
+/// colours are used if `COLORTERM` is `truecolor` or `24bit`, the 256-colour palette if `TERM`
+/// mentions `256color`, and the 16 standard colours if not.
+pub fn detect_color_depth() -> Option<ColorDepth> {
+    color_depth_for(
+        &env::var("TERM").unwrap_or_default(),
+        &env::var("COLORTERM").unwrap_or_default(),
+        colored::control::SHOULD_COLORIZE.should_colorize(),
+    )
+}
+
+/// Works out the range of colours for `detect_color_depth` from the values of `TERM` and
+/// `COLORTERM`, and whether `colored` would use colours.
+fn color_depth_for(term: &str, colorterm: &str, colorize: bool) -> Option<ColorDepth> {
+    if term == "dumb" || !colorize {
+        return None;
+    }
+    let colorterm = colorterm.to_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        Some(ColorDepth::TrueColor)
+    } else if term.contains("256color") {
+        Some(ColorDepth::Ansi256)
+    } else {
+        Some(ColorDepth::Ansi16)
+    }
+}
+
+/// When a `Canvas` resets the colour while it is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorReset {
+    /// Reset the colour after every coloured character, so each one stands on its own.
+    #[default]
+    EachCell,
+    /// Only switch colours when they change, carrying them across characters and rows, and
+    /// reset the colour once at the end. This keeps drawings small and doesn’t disturb the
+    /// styling of any text around them.
+    End,
+}
+
+/// Returns the Braille pattern with the given dots, as laid out in `PIXEL_MAP`. This is how a
+/// `Canvas` draws its pixels by default.
+pub fn braille(dots: u8) -> char {
+    char::from_u32(0x2800 + dots as u32).unwrap()
+}
+
+/// Returns an ASCII character that gets denser the more dots are set, for use with
+/// `Canvas::set_glyphs` on terminals that can’t show Braille.
+pub fn ascii_glyph(dots: u8) -> char {
+    match dots.count_ones() {
+        0 => ' ',
+        n => b".:-=+*#@"[n as usize - 1] as char,
+    }
+}
+
+/// A theme for terminals with a light background, which darkens the named colours that would
+/// otherwise be hard to read on white.
+pub fn light_theme(color: PixelColor) -> PixelColor {
+    match color {
+        PixelColor::White => PixelColor::Black,
+        PixelColor::BrightWhite => PixelColor::BrightBlack,
+        PixelColor::BrightYellow => PixelColor::Yellow,
+        PixelColor::BrightCyan => PixelColor::Cyan,
+        PixelColor::BrightGreen => PixelColor::Green,
+        color => color,
+    }
+}
+
+/// Axes of symmetry about a centre point, used to mirror drawing on a `Canvas`.
+///
+/// The default value has no axes enabled, so nothing is mirrored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Symmetry {
+    /// The x coordinate of the centre point.
+    pub x: u32,
+    /// The y coordinate of the centre point.
+    pub y: u32,
+    /// Mirror across the horizontal axis through the centre (flipping top and bottom).
+    pub horizontal: bool,
+    /// Mirror across the vertical axis through the centre (flipping left and right).
+    pub vertical: bool,
+    /// Mirror across the diagonal through the centre (swapping x and y).
+    pub diagonal: bool,
+}
+
+impl Symmetry {
+    /// Creates a new `Symmetry` about the given centre point, with no axes enabled.
+    pub fn new(x: u32, y: u32) -> Symmetry {
+        Symmetry {
+            x,
+            y,
+            ..Symmetry::default()
+        }
+    }
+
+    /// Enables mirroring across the horizontal axis, and returns the `Symmetry` for use again.
+    pub fn horizontal(mut self) -> Symmetry {
+        self.horizontal = true;
+        self
+    }
+
+    /// Enables mirroring across the vertical axis, and returns the `Symmetry` for use again.
+    pub fn vertical(mut self) -> Symmetry {
+        self.vertical = true;
+        self
+    }
+
+    /// Enables mirroring across the diagonal, and returns the `Symmetry` for use again.
+    pub fn diagonal(mut self) -> Symmetry {
+        self.diagonal = true;
+        self
+    }
+
+    /// Returns whether any axis is enabled.
+    fn is_mirrored(&self) -> bool {
+        self.horizontal || self.vertical || self.diagonal
+    }
+}
+
+/// A ‘turtle’ that can walk around a canvas drawing lines.
+///
+/// By default the turtle draws onto a Braille `Canvas`, but it can draw onto anything that
+/// implements `PixelCanvas`.
+pub struct Turtle<C = Canvas> {
+    pub x: f32,
+    pub y: f32,
+    pub brush: bool,
+    pub erase: bool,
+    pub use_color: bool,
+    pub brush_color: PixelColor,
+    pub rotation: f32,
+    pub radians: bool,
+    pub cvs: C,
+}
+
+impl Turtle {
+    /// Create a new `Turtle`, starting at the given coordinates.
+    ///
+    /// The turtle starts with its brush down, facing right.
+    pub fn new(x: f32, y: f32) -> Turtle {
+        Turtle::with_canvas(x, y, Canvas::new(0, 0))
+    }
+
+    /// Creates a new `Turtle` with the provided `Canvas`, starting at the given coordinates.
+    ///
+    /// The turtle starts with its brush down, facing right.
+    pub fn from_canvas(x: f32, y: f32, cvs: Canvas) -> Turtle {
+        Turtle::with_canvas(x, y, cvs)
+    }
+
+    /// Makes the brush cycle through the hues of the rainbow, advancing by `step` degrees per
+    /// step walked.
+    pub fn rainbow(&mut self, step: f32) {
+        self.cvs.rainbow(step);
+    }
+
+    /// Stops the brush from cycling through the hues of the rainbow.
+    pub fn no_rainbow(&mut self) {
+        self.cvs.no_rainbow();
+    }
+
+    /// Sets the width of a `Turtle`’s `Canvas`, and return it for use again.
+    pub fn width(mut self, width: u32) -> Turtle {
+        self.cvs.width = width as u16;
+        self
+    }
+
+    /// Sets the height of a `Turtle`’s `Canvas`, and return it for use again.
+    pub fn height(mut self, height: u32) -> Turtle {
+        self.cvs.height = height as u16;
+        self
+    }
+}
+
+impl<C: PixelCanvas> Turtle<C> {
+    /// Creates a new `Turtle` that draws onto the given canvas, starting at the given
+    /// coordinates.
+    ///
+    /// The turtle starts with its brush down, facing right.
+    pub fn with_canvas(x: f32, y: f32, cvs: C) -> Turtle<C> {
+        Turtle {
+            cvs,
+            x,
+            y,
+            brush: true,
+            erase: false,
+            use_color: false,
+            brush_color: PixelColor::White,
+            rotation: 0.0,
+            radians: false,
+        }
+    }
+
+    /// Makes the `Turtle` measure angles in radians instead of degrees, converting its current
+    /// rotation.
+    ///
+    /// This affects every method that takes an angle, turtle scripts and the `rotation` field.
+    pub fn use_radians(&mut self) {
+        if !self.radians {
+            self.rotation = degrees_to_radians(self.rotation);
+            self.radians = true;
+        }
+    }
+
+    /// Makes the `Turtle` measure angles in degrees again, converting its current rotation.
+    pub fn use_degrees(&mut self) {
+        if self.radians {
+            self.rotation = self.rotation.to_degrees();
+            self.radians = false;
+        }
+    }
+
+    /// Lifts the `Turtle`’s brush.
+    pub fn up(&mut self) {
+        self.brush = false;
+    }
+
+    /// Puts down the `Turtle`’s brush.
+    pub fn down(&mut self) {
+        self.brush = true;
+    }
+
+    /// Toggles the `Turtle`’s brush.
+    pub fn toggle(&mut self) {
+        self.brush = !self.brush;
+    }
+
+    /// Makes the `Turtle` erase the pixels along its path instead of drawing them, while its
+    /// brush is down.
+    pub fn pen_erase(&mut self) {
+        self.erase = true;
+    }
+
+    /// Makes the `Turtle` draw along its path again after `pen_erase`.
+    pub fn pen_draw(&mut self) {
+        self.erase = false;
+    }
+
+    /// Use specific color the the brush.
+    pub fn color(&mut self, brush_color: PixelColor) {
+        self.use_color = true;
+        self.brush_color = brush_color;
+    }
+
+    /// Remove color from brush.
+    pub fn clean_brush(&mut self) {
+        self.use_color = false;
+    }
+
+    /// Moves the `Turtle` forward by `dist` steps.
+    pub fn forward(&mut self, dist: f32) {
+        let rotation = self.rotation;
+        self.teleport_polar(dist, rotation);
+    }
+
+    /// Moves the `Turtle` backward by `dist` steps.
+    pub fn back(&mut self, dist: f32) {
+        self.forward(-dist);
+    }
+
+    /// Teleports the `Turtle` to the given coordinates.
+    ///
+    /// Note that this draws a line between the old position and the new one if the `Turtle`’s
+    /// brush is down.
+    pub fn teleport(&mut self, x: f32, y: f32) {
+        let (mut from_x, mut from_y, mut x, mut y) = (self.x, self.y, x, y);
+        let wrap = self.cvs.wrapping();
+        if let Some((w, h)) = wrap {
+            // Move the line onto positive coordinates, and leave the canvas to wrap it.
+            let shift = |from: &mut f32, to: &mut f32, size: u32| {
+                if size > 0 {
+                    let size = size as f32;
+                    let offset = (-from.min(*to) / size).ceil().max(0.0) * size;
+                    *from += offset;
+                    *to += offset;
+                }
+            };
+            shift(&mut from_x, &mut x, w);
+            shift(&mut from_y, &mut y, h);
+        }
+
+        if self.brush {
+            if self.erase {
+                self.cvs.unset_line(
+                    cmp::max(0, from_x.round() as i32) as u32,
+                    cmp::max(0, from_y.round() as i32) as u32,
+                    cmp::max(0, x.round() as i32) as u32,
+                    cmp::max(0, y.round() as i32) as u32,
+                );
+            } else if self.use_color {
+                self.cvs.line_colored(
+                    cmp::max(0, from_x.round() as i32) as u32,
+                    cmp::max(0, from_y.round() as i32) as u32,
+                    cmp::max(0, x.round() as i32) as u32,
+                    cmp::max(0, y.round() as i32) as u32,
+                    self.brush_color,
+                );
+            } else {
+                self.cvs.line(
+                    cmp::max(0, from_x.round() as i32) as u32,
+                    cmp::max(0, from_y.round() as i32) as u32,
+                    cmp::max(0, x.round() as i32) as u32,
+                    cmp::max(0, y.round() as i32) as u32,
+                );
+            }
+        }
+
+        match wrap {
+            Some((w, h)) => {
+                self.x = if w > 0 { x.rem_euclid(w as f32) } else { x };
+                self.y = if h > 0 { y.rem_euclid(h as f32) } else { y };
+            }
+            None => {
+                self.x = x;
+                self.y = y;
+            }
+        }
+    }
+
+    /// Teleports the `Turtle` by `distance` steps in the direction of `angle` degrees (or
+    /// radians, after `use_radians`), measured clockwise from facing right, without changing
+    /// which way it is facing.
+    ///
+    /// As with `teleport`, this draws a line if the `Turtle`’s brush is down.
+    pub fn teleport_polar(&mut self, distance: f32, angle: f32) {
+        let angle = if self.radians {
+            angle
+        } else {
+            degrees_to_radians(angle)
+        };
+        let x = self.x + angle.cos() * distance;
+        let y = self.y + angle.sin() * distance * self.cvs.pixel_aspect();
+        self.teleport(x, y);
+    }
+
+    /// Moves the `Turtle` forward by `dist` steps as if it were facing `angle` degrees (or
+    /// radians, after `use_radians`), without changing which way it is actually facing.
+    ///
+    /// The arguments come in the same order as for `teleport_polar`.
+    pub fn forward_to_angle(&mut self, dist: f32, angle: f32) {
+        self.teleport_polar(dist, angle);
+    }
+
+    /// Turns the `Turtle` right (clockwise) by `angle` degrees (or radians, after
+    /// `use_radians`).
+    pub fn right(&mut self, angle: f32) {
+        self.rotation += angle;
+    }
+
+    /// Turns the `Turtle` left (anticlockwise) by `angle` degrees (or radians, after
+    /// `use_radians`).
+    pub fn left(&mut self, angle: f32) {
+        self.rotation -= angle;
+    }
+
+    /// Runs a script of whitespace-separated turtle commands.
+    ///
+    /// Each command is a letter, optionally followed by an argument:
+    ///
+    /// * `F<dist>` and `B<dist>` move forward and backward,
+    /// * `R<angle>` and `L<angle>` turn right and left,
+    /// * `U` and `D` lift and put down the brush,
+    /// * `C<colour>` uses the given colour for the brush (anything accepted by `parse_color`),
+    ///   and `C` on its own removes the colour.
+    ///
+    /// For example, `"F100 R90 F100 U F20 D C#ff0000 F50"`. Letters are case-insensitive. The
+    /// whole script is checked before anything is drawn, so nothing happens if it is invalid;
+    /// arguments that aren’t finite numbers, such as `inf`, are invalid too.
+    pub fn run(&mut self, commands: &str) -> Result<(), ParseScriptError> {
+        enum Step {
+            Forward(f32),
+            Right(f32),
+            Up,
+            Down,
+            Color(Option<PixelColor>),
+        }
+
+        let mut steps = Vec::new();
+        for token in commands.split_whitespace() {
+            let err = || ParseScriptError {
+                token: token.to_owned(),
+            };
+            let mut chars = token.chars();
+            let op = chars.next().unwrap().to_ascii_uppercase();
+            let arg = chars.as_str();
+            // Infinite distances would draw lines billions of pixels long.
+            let num = || {
+                arg.parse::<f32>()
+                    .ok()
+                    .filter(|a| a.is_finite())
+                    .ok_or_else(err)
+            };
+            steps.push(match op {
+                'F' => Step::Forward(num()?),
+                'B' => Step::Forward(-num()?),
+                'R' => Step::Right(num()?),
+                'L' => Step::Right(-num()?),
+                'U' if arg.is_empty() => Step::Up,
+                'D' if arg.is_empty() => Step::Down,
+                'C' if arg.is_empty() => Step::Color(None),
+                'C' => Step::Color(Some(parse_color(arg).map_err(|_| err())?)),
+                _ => return Err(err()),
+            });
+        }
+
+        for step in steps {
+            match step {
+                Step::Forward(dist) => self.forward(dist),
+                Step::Right(angle) => self.right(angle),
+                Step::Up => self.up(),
+                Step::Down => self.down(),
+                Step::Color(Some(color)) => self.color(color),
+                Step::Color(None) => self.clean_brush(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the `Turtle`’s canvas to a `String` and returns it.
+    pub fn frame(&self) -> String {
+        self.cvs.frame()
+    }
+}
+
+/// A surface made of pixels that a `Turtle` can draw onto, and that recorded `Command`s can be
+/// replayed onto.
+///
+/// Only `set`, `set_colored`, `unset` and `frame` need to be implemented; everything else is
+/// drawn pixel by pixel unless a canvas has a better way of drawing it.
+pub trait PixelCanvas {
+    /// Sets a pixel at the specified coordinates.
+    fn set(&mut self, x: u32, y: u32);
+
+    /// Sets a pixel at the specified coordinates, specifying its colour.
+    fn set_colored(&mut self, x: u32, y: u32, color: PixelColor);
+
+    /// Deletes a pixel at the specified coordinates.
+    fn unset(&mut self, x: u32, y: u32);
+
+    /// Draws the canvas to a `String` and returns it.
+    fn frame(&self) -> String;
+
+    /// Returns the width of each pixel divided by its height, which a `Turtle` uses to keep
+    /// its drawings in proportion. The default is `1.0`, for square pixels.
+    fn pixel_aspect(&self) -> f32 {
+        1.0
+    }
+
+    /// Returns the size of the canvas in pixels if coordinates wrap around its edges, so that
+    /// a `Turtle` walking off one edge comes back on the opposite one. The default is `None`.
+    fn wrapping(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    /// Draws a line from `(x1, y1)` to `(x2, y2)`.
+    fn line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        for (x, y) in line_points(x1, y1, x2, y2) {
+            self.set(x, y);
+        }
+    }
+
+    /// Draws a line from `(x1, y1)` to `(x2, y2)` in the given colour.
+    fn line_colored(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: PixelColor) {
+        for (x, y) in line_points(x1, y1, x2, y2) {
+            self.set_colored(x, y, color);
+        }
+    }
+
+    /// Deletes the pixels along a line from `(x1, y1)` to `(x2, y2)`.
+    fn unset_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        for (x, y) in line_points(x1, y1, x2, y2) {
+            self.unset(x, y);
+        }
+    }
+
+    /// Draws an ellipse centred on `(xm, ym)`, with a horizontal radius of `a` pixels and a
+    /// vertical radius of `b` horizontal pixels, as `Canvas::ellipse_center` does.
+    fn ellipse_center(&mut self, xm: u32, ym: u32, a: u32, b: u32) {
+        let b = (b as f32 * self.pixel_aspect()).round() as u32;
+        for (x, y) in ellipse_points(xm, ym, a, b) {
+            self.set(x, y);
+        }
+    }
+
+    /// Draws an ellipse in the given colour, as `Canvas::ellipse_center_colored` does.
+    fn ellipse_center_colored(&mut self, xm: u32, ym: u32, a: u32, b: u32, color: PixelColor) {
+        let b = (b as f32 * self.pixel_aspect()).round() as u32;
+        for (x, y) in ellipse_points(xm, ym, a, b) {
+            self.set_colored(x, y, color);
+        }
+    }
+
+    /// Draws part of an ellipse from `start_deg` to `end_deg` degrees, as `Canvas::ellipse_arc`
+    /// does.
+    fn ellipse_arc(&mut self, xm: u32, ym: u32, a: u32, b: u32, start_deg: f32, end_deg: f32) {
+        let b = (b as f32 * self.pixel_aspect()).round() as u32;
+        let tolerance = raster::DEFAULT_TOLERANCE;
+        for ((x1, y1), (x2, y2)) in
+            raster::arc_segments(xm, ym, a, b, start_deg, end_deg, tolerance)
+        {
+            self.line(x1, y1, x2, y2);
+        }
+    }
+
+    /// Fills the polygon with the given corners, including its outline, as
+    /// `Canvas::fill_polygon` does.
+    fn fill_polygon(&mut self, points: &[(u32, u32)]) {
+        for (x, y) in polygon_points(points) {
+            self.set(x, y);
+        }
+    }
+
+    /// Fills the polygon with the given corners in the given colour.
+    fn fill_polygon_colored(&mut self, points: &[(u32, u32)], color: PixelColor) {
+        for (x, y) in polygon_points(points) {
+            self.set_colored(x, y, color);
+        }
+    }
+
+    /// Sets a fraction `density` of the pixels in a region, as `Canvas::fill_rect_density` does.
+    fn fill_rect_density(&mut self, x: u32, y: u32, width: u32, height: u32, density: f32) {
+        for (px, py) in rect_density_points(x, y, width, height, density) {
+            self.set(px, py);
+        }
+    }
+
+    /// Sets a fraction `density` of the pixels inside an ellipse, as
+    /// `Canvas::fill_ellipse_density` does.
+    fn fill_ellipse_density(&mut self, xm: u32, ym: u32, a: u32, b: u32, density: f32) {
+        let b = (b as f32 * self.pixel_aspect()).round() as u32;
+        for (px, py) in ellipse_density_points(xm, ym, a, b, density) {
+            self.set(px, py);
+        }
+    }
+
+    /// Copies a packed buffer of pixels onto the canvas, as `Canvas::set_rows_from_bits` does.
+    fn set_rows_from_bits(&mut self, x: u32, y: u32, width: u32, data: &[u8]) {
+        let stride = width.div_ceil(8) as usize;
+        if stride == 0 {
+            return;
+        }
+        for (j, row) in data.chunks(stride).enumerate() {
+            for i in 0..width {
+                let on = row
+                    .get(i as usize / 8)
+                    .is_some_and(|b| b & (0x80 >> (i % 8)) != 0);
+                if on {
+                    self.set(x + i, y + j as u32);
+                } else {
+                    self.unset(x + i, y + j as u32);
+                }
+            }
+        }
+    }
+
+    /// Sets the pixels of a small bitmap, as `Canvas::stamp` does.
+    fn stamp(&mut self, x: u32, y: u32, width: u32, rows: &[bool]) {
+        if width == 0 {
+            return;
+        }
+        for (j, row) in rows.chunks(width as usize).enumerate() {
+            for (i, _) in row.iter().enumerate().filter(|&(_, &on)| on) {
+                self.set(x + i as u32, y + j as u32);
+            }
+        }
+    }
+
+    /// Toggles a pixel at the specified coordinates. Canvases that can’t tell which of their
+    /// pixels are set only set it, which is the default.
+    fn toggle(&mut self, x: u32, y: u32) {
+        self.set(x, y);
+    }
+
+    /// Deletes everything drawn on the canvas. The default does nothing.
+    fn clear(&mut self) {}
+
+    /// Sets a letter at the specified coordinates. Canvases that can’t show letters ignore
+    /// them, which is the default.
+    fn set_char(&mut self, x: u32, y: u32, c: char) {
+        let _ = (x, y, c);
+    }
+
+    /// Draws text at the specified coordinates (top-left of the text) up to `max_width`
+    /// pixels across, one letter at a time with `set_char`.
+    fn text(&mut self, x: u32, y: u32, max_width: u32, text: &str) {
+        let mut w = 0;
+        for c in text.chars() {
+            if w > max_width {
+                return;
+            }
+            if char_width(c) > 0 {
+                self.set_char(x + w, y, c);
+            }
+            w += char_width(c) * 2;
+        }
+    }
+}
+
+impl PixelCanvas for Canvas {
+    fn set(&mut self, x: u32, y: u32) {
+        Canvas::set(self, x, y);
+    }
+
+    fn set_colored(&mut self, x: u32, y: u32, color: PixelColor) {
+        Canvas::set_colored(self, x, y, color);
+    }
+
+    fn unset(&mut self, x: u32, y: u32) {
+        Canvas::unset(self, x, y);
+    }
+
+    fn frame(&self) -> String {
+        Canvas::frame(self)
+    }
+
+    fn pixel_aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    fn wrapping(&self) -> Option<(u32, u32)> {
+        Canvas::wrapping(self)
+    }
+
+    fn line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        Canvas::line(self, x1, y1, x2, y2);
+    }
+
+    fn line_colored(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: PixelColor) {
+        Canvas::line_colored(self, x1, y1, x2, y2, color);
+    }
+
+    fn unset_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        Canvas::unset_line(self, x1, y1, x2, y2);
+    }
+
+    fn ellipse_center(&mut self, xm: u32, ym: u32, a: u32, b: u32) {
+        Canvas::ellipse_center(self, xm, ym, a, b);
+    }
+
+    fn ellipse_center_colored(&mut self, xm: u32, ym: u32, a: u32, b: u32, color: PixelColor) {
+        Canvas::ellipse_center_colored(self, xm, ym, a, b, color);
+    }
+
+    fn ellipse_arc(&mut self, xm: u32, ym: u32, a: u32, b: u32, start_deg: f32, end_deg: f32) {
+        Canvas::ellipse_arc(self, xm, ym, a, b, start_deg, end_deg);
+    }
+
+    fn fill_polygon(&mut self, points: &[(u32, u32)]) {
+        Canvas::fill_polygon(self, points);
+    }
+
+    fn fill_polygon_colored(&mut self, points: &[(u32, u32)], color: PixelColor) {
+        Canvas::fill_polygon_colored(self, points, color);
+    }
+
+    fn fill_rect_density(&mut self, x: u32, y: u32, width: u32, height: u32, density: f32) {
+        Canvas::fill_rect_density(self, x, y, width, height, density);
+    }
+
+    fn fill_ellipse_density(&mut self, xm: u32, ym: u32, a: u32, b: u32, density: f32) {
+        Canvas::fill_ellipse_density(self, xm, ym, a, b, density);
+    }
+
+    fn set_rows_from_bits(&mut self, x: u32, y: u32, width: u32, data: &[u8]) {
+        Canvas::set_rows_from_bits(self, x, y, width, data);
+    }
+
+    fn stamp(&mut self, x: u32, y: u32, width: u32, rows: &[bool]) {
+        Canvas::stamp(self, x, y, width, rows);
+    }
+
+    fn toggle(&mut self, x: u32, y: u32) {
+        Canvas::toggle(self, x, y);
+    }
+
+    fn clear(&mut self) {
+        Canvas::clear(self);
+    }
+
+    fn set_char(&mut self, x: u32, y: u32, c: char) {
+        Canvas::set_char(self, x, y, c);
+    }
+
+    fn text(&mut self, x: u32, y: u32, max_width: u32, text: &str) {
+        Canvas::text(self, x, y, max_width, text);
+    }
+}
+
+/// An error returned when a turtle script contains an invalid command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseScriptError {
+    token: String,
+}
+
+impl fmt::Display for ParseScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid turtle command: `{}`", self.token)
+    }
+}
+
+impl Error for ParseScriptError {}
+
+fn degrees_to_radians(deg: f32) -> f32 {
     deg * (f32::consts::PI / 180.0f32)
 }
+
+/// Returns a pseudo-random number between `0.0` and `1.0` for the given pixel, which is always
+/// the same for the same pixel.
+fn noise(x: u32, y: u32) -> f32 {
+    let mut h = (x as u64) << 32 | y as u64;
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^= h >> 33;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Returns the pixels set by `Canvas::fill_rect_density`.
+fn rect_density_points(x: u32, y: u32, width: u32, height: u32, density: f32) -> Vec<Point> {
+    let rows = y..y.saturating_add(height);
+    rows.flat_map(|py| (x..x.saturating_add(width)).map(move |px| (px, py)))
+        .filter(|&(px, py)| noise(px, py) < density)
+        .collect()
+}
+
+/// Returns the pixels set by `Canvas::fill_ellipse_density`, for a vertical radius that has
+/// already been scaled by the pixel aspect.
+fn ellipse_density_points(xm: u32, ym: u32, a: u32, b: u32, density: f32) -> Vec<Point> {
+    let (fa, fb) = (cmp::max(a, 1) as f32, cmp::max(b, 1) as f32);
+    let rows = ym.saturating_sub(b)..=ym.saturating_add(b);
+    let cols = xm.saturating_sub(a)..=xm.saturating_add(a);
+    rows.flat_map(|py| cols.clone().map(move |px| (px, py)))
+        .filter(|&(px, py)| {
+            let dx = (px as f32 - xm as f32) / fa;
+            let dy = (py as f32 - ym as f32) / fb;
+            dx * dx + dy * dy <= 1.0 && noise(px, py) < density
+        })
+        .collect()
+}
+
+/// Breaks text into lines no wider than `max_width` pixels, between words where possible.
+fn wrap_text(text: &str, max_width: u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let fits = |line: &str| Canvas::text_extent(line).0 <= max_width;
+            let joined = if line.is_empty() {
+                word.to_owned()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if fits(&joined) {
+                line = joined;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(line);
+            }
+            // Words too long for a line of their own are broken wherever they have to be.
+            line = String::new();
+            for c in word.chars() {
+                line.push(c);
+                if !fits(&line) && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(line);
+                    line = c.to_string();
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Returns the number of terminal columns taken up by a character: `0` for combining marks,
+/// zero-width spaces and joiners and variation selectors, `2` for wide characters such as CJK
+/// ideographs, Hangul, full-width forms and most emoji, and `1` for everything else.
+fn char_width(c: char) -> u32 {
+    match c as u32 {
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x2060..=0x2064
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0xFEFF
+        | 0xE0100..=0xE01EF => 0,
+        // The emoji among the older symbol blocks, which terminals draw two columns wide.
+        0x231A..=0x231B
+        | 0x23E9..=0x23EC
+        | 0x23F0
+        | 0x23F3
+        | 0x25FD..=0x25FE
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267F
+        | 0x2693
+        | 0x26A1
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5
+        | 0x26CE
+        | 0x26D4
+        | 0x26EA
+        | 0x26F2..=0x26F3
+        | 0x26F5
+        | 0x26FA
+        | 0x26FD
+        | 0x2705
+        | 0x270A..=0x270B
+        | 0x2728
+        | 0x274C
+        | 0x274E
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27B0
+        | 0x27BF
+        | 0x2B1B..=0x2B1C
+        | 0x2B50
+        | 0x2B55 => 2,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F004
+        | 0x1F0CF
+        | 0x1F18E
+        | 0x1F191..=0x1F19A
+        | 0x1F200..=0x1F251
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F7E0..=0x1F7EB
+        | 0x1F90C..=0x1F9FF
+        | 0x1FA70..=0x1FAFF
+        | 0x20000..=0x2FFFD
+        | 0x30000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn hue_to_color(hue: f32) -> PixelColor {
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    PixelColor::TrueColor {
+        r: (r * 255.0f32).round() as u8,
+        g: (g * 255.0f32).round() as u8,
+        b: (b * 255.0f32).round() as u8,
+    }
+}
+
+fn blend(over: PixelColor, under: PixelColor, opacity: f32) -> PixelColor {
+    let (r, g, b) = match over {
+        PixelColor::TrueColor { r, g, b } => (r, g, b),
+        _ => return over,
+    };
+    let (ur, ug, ub) = match under {
+        PixelColor::TrueColor { r, g, b } => (r, g, b),
+        _ => (0, 0, 0),
+    };
+    let mix = |o: u8, u: u8| (o as f32 * opacity + u as f32 * (1.0 - opacity)).round() as u8;
+    PixelColor::TrueColor {
+        r: mix(r, ur),
+        g: mix(g, ug),
+        b: mix(b, ub),
+    }
+}
+
+/// The usual appearance of the 16 standard terminal colours.
+static ANSI_COLORS: [(PixelColor, (u8, u8, u8)); 16] = [
+    (PixelColor::Black, (0, 0, 0)),
+    (PixelColor::Red, (205, 0, 0)),
+    (PixelColor::Green, (0, 205, 0)),
+    (PixelColor::Yellow, (205, 205, 0)),
+    (PixelColor::Blue, (0, 0, 238)),
+    (PixelColor::Magenta, (205, 0, 205)),
+    (PixelColor::Cyan, (0, 205, 205)),
+    (PixelColor::White, (229, 229, 229)),
+    (PixelColor::BrightBlack, (127, 127, 127)),
+    (PixelColor::BrightRed, (255, 0, 0)),
+    (PixelColor::BrightGreen, (0, 255, 0)),
+    (PixelColor::BrightYellow, (255, 255, 0)),
+    (PixelColor::BrightBlue, (92, 92, 255)),
+    (PixelColor::BrightMagenta, (255, 0, 255)),
+    (PixelColor::BrightCyan, (0, 255, 255)),
+    (PixelColor::BrightWhite, (255, 255, 255)),
+];
+
+/// Returns the standard terminal colour that looks closest to `color`.
+fn nearest_ansi(color: PixelColor) -> PixelColor {
+    let (r, g, b) = match color {
+        PixelColor::TrueColor { r, g, b } => (r as f32, g as f32, b as f32),
+        _ => return color,
+    };
+    // The ‘redmean’ distance, which weights the channels roughly as the eye does.
+    let distance = |&(_, (r2, g2, b2)): &(PixelColor, (u8, u8, u8))| {
+        let mean = (r + r2 as f32) / 2.0;
+        let (dr, dg, db) = (r - r2 as f32, g - g2 as f32, b - b2 as f32);
+        (2.0 + mean / 256.0) * dr * dr + 4.0 * dg * dg + (2.0 + (255.0 - mean) / 256.0) * db * db
+    };
+    ANSI_COLORS
+        .iter()
+        .min_by(|a, b| {
+            distance(a)
+                .partial_cmp(&distance(b))
+                .unwrap_or(cmp::Ordering::Equal)
+        })
+        .map_or(color, |&(ansi, _)| ansi)
+}
+
+/// Returns the index of the colour in the 256-colour palette that looks closest to the given
+/// true colour, picking from its 6×6×6 cube of colours and its ramp of greys.
+fn nearest_indexed(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (v as u32 - 35) / 40,
+    } as u8;
+    let (cr, cg, cb) = (level(r), level(g), level(b));
+    let cube = (
+        LEVELS[cr as usize],
+        LEVELS[cg as usize],
+        LEVELS[cb as usize],
+    );
+
+    let mean = (r as u32 + g as u32 + b as u32) / 3;
+    let grey_index = (mean.saturating_sub(3) / 10).min(23) as u8;
+    let grey = 8 + 10 * grey_index;
+
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+    if distance((grey, grey, grey)) < distance(cube) {
+        232 + grey_index
+    } else {
+        16 + 36 * cr + 6 * cg + cb
+    }
+}
+
+fn color_key(color: PixelColor) -> (u8, u8, u8, u8) {
+    match color {
+        PixelColor::Black => (0, 0, 0, 0),
+        PixelColor::Red => (1, 0, 0, 0),
+        PixelColor::Green => (2, 0, 0, 0),
+        PixelColor::Yellow => (3, 0, 0, 0),
+        PixelColor::Blue => (4, 0, 0, 0),
+        PixelColor::Magenta => (5, 0, 0, 0),
+        PixelColor::Cyan => (6, 0, 0, 0),
+        PixelColor::White => (7, 0, 0, 0),
+        PixelColor::BrightBlack => (8, 0, 0, 0),
+        PixelColor::BrightRed => (9, 0, 0, 0),
+        PixelColor::BrightGreen => (10, 0, 0, 0),
+        PixelColor::BrightYellow => (11, 0, 0, 0),
+        PixelColor::BrightBlue => (12, 0, 0, 0),
+        PixelColor::BrightMagenta => (13, 0, 0, 0),
+        PixelColor::BrightCyan => (14, 0, 0, 0),
+        PixelColor::BrightWhite => (15, 0, 0, 0),
+        PixelColor::TrueColor { r, g, b } => (16, r, g, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ascii_glyph, braille, color_depth_for, light_theme, line_points, nearest_ansi,
+        nearest_indexed, Canvas, ColorMode, ColorReset, Palette, PixelColor, Symmetry, Turtle,
+    };
+
+    fn palette() -> Palette {
+        Palette::new()
+            .color("red", PixelColor::Red)
+            .color("green", PixelColor::Green)
+            .color("blue", PixelColor::Blue)
+    }
+
+    /// Returns a new `Canvas` with the test palette whose colours are always drawn.
+    fn indexed_canvas() -> Canvas {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_color_mode(ColorMode::Always);
+        canvas.set_palette(palette());
+        canvas
+    }
+
+    /// Prints `frame` on an imaginary terminal of plain characters, then `diff` over it, and
+    /// returns what the screen shows.
+    fn apply_diff(frame: &str, diff: &str) -> Vec<String> {
+        let mut screen: Vec<Vec<char>> = frame.lines().map(|l| l.chars().collect()).collect();
+        let (mut row, mut col) = (0, 0);
+        let mut rest = diff;
+        while !rest.is_empty() {
+            if let Some(escape) = rest.strip_prefix("\x1b[") {
+                let end = escape.find('H').unwrap();
+                let mut at = escape[..end]
+                    .split(';')
+                    .map(|n| n.parse::<usize>().unwrap());
+                row = at.next().unwrap() - 1;
+                col = at.next().unwrap() - 1;
+                rest = &escape[end + 1..];
+                continue;
+            }
+            let c = rest.chars().next().unwrap();
+            while screen.len() <= row {
+                screen.push(Vec::new());
+            }
+            while screen[row].len() <= col {
+                screen[row].push(' ');
+            }
+            screen[row][col] = c;
+            col += 1;
+            rest = &rest[c.len_utf8()..];
+        }
+        screen.iter().map(|l| l.iter().collect()).collect()
+    }
+
+    /// Checks that printing `current.frame_diff(previous)` over `previous` shows `current`.
+    fn check_diff(previous: &Canvas, current: &Canvas) {
+        let diff = current.frame_diff(previous);
+        let screen = apply_diff(&previous.frame(), &diff);
+        let expected: Vec<String> = current.frame().lines().map(String::from).collect();
+        for (i, line) in screen.iter().enumerate() {
+            let want = expected.get(i).map_or("", |l| l.as_str());
+            assert_eq!(
+                line.trim_end(),
+                want.trim_end(),
+                "row {} after {:?}",
+                i,
+                diff
+            );
+        }
+        assert!(screen.len() >= expected.len());
+    }
+
+    fn plain_canvas() -> Canvas {
+        let mut canvas = Canvas::new(10, 8);
+        canvas.set_color_mode(ColorMode::Never);
+        canvas
+    }
+
+    #[test]
+    fn frame_diff_changes_cells() {
+        let mut previous = plain_canvas();
+        previous.line(0, 0, 19, 7);
+        previous.text(0, 8, 20, "abc");
+        let mut current = previous.clone();
+        assert_eq!(current.frame_diff(&previous), "");
+
+        current.set(14, 1);
+        current.set_char(2, 8, 'X');
+        check_diff(&previous, &current);
+        let diff = current.frame_diff(&previous);
+        assert!(diff.contains('X') && !diff.contains('a') && !diff.contains('c'));
+    }
+
+    #[test]
+    fn frame_diff_erases_cells() {
+        let mut previous = plain_canvas();
+        previous.line(0, 0, 19, 7);
+        previous.set_char(10, 4, 'q');
+        let mut current = previous.clone();
+        current.unset_line(0, 0, 7, 3);
+        current.set_char(10, 4, ' ');
+        check_diff(&previous, &current);
+
+        current.clear();
+        check_diff(&previous, &current);
+    }
+
+    #[test]
+    fn frame_diff_shrinks() {
+        let mut previous = Canvas::new(0, 0);
+        previous.set_color_mode(ColorMode::Never);
+        previous.line(0, 0, 39, 23);
+        previous.text(30, 20, 20, "far");
+        let mut current = Canvas::new(0, 0);
+        current.set_color_mode(ColorMode::Never);
+        current.line(0, 0, 9, 3);
+        check_diff(&previous, &current);
+    }
+
+    fn rgb(r: u8, g: u8, b: u8) -> PixelColor {
+        PixelColor::TrueColor { r, g, b }
+    }
+
+    #[test]
+    fn interpolate_ends() {
+        let mut from = Canvas::new(20, 8);
+        from.line(0, 0, 19, 7);
+        from.set_colored(3, 5, rgb(200, 0, 0));
+        from.set_char(10, 0, 'a');
+        let mut to = Canvas::new(10, 12);
+        to.line(0, 7, 19, 0);
+        to.set_colored(3, 5, rgb(0, 0, 100));
+        to.set_char(10, 8, 'b');
+
+        assert!(Canvas::interpolate(&from, &to, 0.0) == from);
+        assert!(Canvas::interpolate(&from, &to, 1.0) == to);
+        assert!(Canvas::interpolate(&from, &to, -1.0) == from);
+        assert!(Canvas::interpolate(&from, &to, 2.0) == to);
+    }
+
+    #[test]
+    fn interpolate_blends_true_colours() {
+        let (mut from, mut to) = (Canvas::new(0, 0), Canvas::new(0, 0));
+        from.set_colored(0, 0, rgb(200, 0, 0));
+        to.set_colored(0, 0, rgb(0, 0, 100));
+        to.set_colored(1, 0, rgb(0, 0, 100));
+        let middle = Canvas::interpolate(&from, &to, 0.5);
+        let mut expected = Canvas::new(0, 0);
+        expected.set_colored(0, 0, rgb(100, 0, 50));
+        // The pixel only in `to` may or may not have dissolved in yet, but takes the colour.
+        if middle.get(1, 0) {
+            expected.set_colored(1, 0, rgb(100, 0, 50));
+        }
+        assert!(middle == expected);
+
+        // Pixels in only one canvas are dissolved in or out gradually.
+        let (mut from, mut to) = (Canvas::new(0, 0), Canvas::new(0, 0));
+        from.extend((0..40).flat_map(|x| (0..40).map(move |y| (x, y))));
+        to.extend((40..80).flat_map(|x| (0..40).map(move |y| (x, y))));
+        let count = |c: &Canvas, xs: ::std::ops::Range<u32>| {
+            xs.flat_map(|x| (0..40).map(move |y| (x, y)))
+                .filter(|&(x, y)| c.get(x, y))
+                .count()
+        };
+        // About a quarter of the way through, about a quarter of each has changed.
+        let quarter = Canvas::interpolate(&from, &to, 0.25);
+        let (left, right) = (count(&quarter, 0..40), count(&quarter, 40..80));
+        assert!(left > 1000 && left < 1400, "{}", left);
+        assert!(right > 200 && right < 600, "{}", right);
+    }
+
+    fn hash_drawing() -> Canvas {
+        let mut canvas = Canvas::new(20, 8);
+        canvas.line(1, 1, 17, 6);
+        canvas.set_colored(18, 0, PixelColor::Red);
+        canvas.set_char(2, 4, 'x');
+        canvas
+    }
+
+    #[test]
+    fn content_hash_ignores_drawing_order() {
+        let a = hash_drawing();
+        let mut b = Canvas::new(20, 8);
+        b.set_char(2, 4, 'x');
+        b.set_colored(18, 0, PixelColor::Red);
+        let mut line: Vec<_> = line_points(1, 1, 17, 6).collect();
+        line.reverse();
+        b.extend(line);
+        // A pixel set and deleted again leaves nothing to see.
+        b.set(0, 7);
+        b.unset(0, 7);
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_eq!(a.content_hash(), a.clone().content_hash());
+    }
+
+    #[test]
+    fn content_hash_sees_every_change() {
+        let hash = hash_drawing().content_hash();
+        let changes: [fn(&mut Canvas); 6] = [
+            |c| c.set(0, 7),
+            // Another pixel of a character that already has some set.
+            |c| c.set(18, 1),
+            |c| c.unset(1, 1),
+            |c| c.set_colored(18, 0, PixelColor::Blue),
+            |c| c.set_char(2, 4, 'y'),
+            |c| c.set_char(40, 4, ' '),
+        ];
+        for (i, change) in changes.iter().enumerate() {
+            let mut canvas = hash_drawing();
+            change(&mut canvas);
+            assert!(canvas.content_hash() != hash, "change {}", i);
+        }
+        let mut bigger = Canvas::new(22, 8);
+        bigger.blit(&hash_drawing(), 0, 0);
+        assert!(bigger.content_hash() != hash);
+    }
+
+    #[test]
+    fn eq_pixels_and_cells() {
+        let mut a = Canvas::new(20, 8);
+        a.line(1, 1, 17, 6);
+        a.set_colored(18, 0, PixelColor::Red);
+        a.set_char(2, 4, 'x');
+
+        let mut b = Canvas::new(40, 40);
+        b.lines_colored(&[((1, 1), (17, 6))], PixelColor::Green);
+        b.set_colored(18, 0, PixelColor::Blue);
+        // A character that was touched but has nothing in it counts as empty.
+        b.set(0, 7);
+        b.unset(0, 7);
+        assert!(a != b);
+        assert!(a.eq_pixels(&b) && b.eq_pixels(&a));
+        assert!(!a.eq_cells(&b) && !b.eq_cells(&a));
+
+        b.set_char(2, 4, 'x');
+        assert!(a.eq_cells(&b) && b.eq_cells(&a));
+        b.set_char(2, 4, 'z');
+        assert!(a.eq_pixels(&b) && !a.eq_cells(&b));
+
+        // One more pixel, in a character that already has some.
+        b.set(18, 1);
+        assert!(!a.eq_pixels(&b) && !b.eq_pixels(&a));
+        b.unset(18, 1);
+        b.unset(1, 1);
+        assert!(!a.eq_pixels(&b) && !b.eq_pixels(&a));
+    }
+
+    /// Returns the pixels set on `canvas` in the top-left 64 by 64 pixels, row by row.
+    fn pixels(canvas: &Canvas) -> Vec<(u32, u32)> {
+        (0..64)
+            .flat_map(|y| (0..64).map(move |x| (x, y)))
+            .filter(|&(x, y)| canvas.get(x, y))
+            .collect()
+    }
+
+    #[test]
+    fn overlaps_at_any_offset() {
+        let mut canvas = Canvas::new(0, 0);
+        canvas.line(0, 0, 15, 9);
+        canvas.line(3, 12, 12, 12);
+        canvas.set_char(20, 0, 'x');
+        // A shape that spreads across characters however it is moved.
+        let shape: Canvas = [(0, 0), (1, 1), (2, 3), (3, 4), (1, 6)]
+            .iter()
+            .cloned()
+            .collect();
+        let shape_pixels = pixels(&shape);
+
+        for dx in -5..18 {
+            for dy in -8..16 {
+                let mut hits: Vec<(u32, u32)> = shape_pixels
+                    .iter()
+                    .map(|&(x, y)| (x as i32 + dx, y as i32 + dy))
+                    .filter(|&(x, y)| x >= 0 && y >= 0 && canvas.get(x as u32, y as u32))
+                    .map(|(x, y)| (x as u32, y as u32))
+                    .collect();
+                hits.sort_by_key(|&(x, y)| (y, x));
+                assert_eq!(canvas.overlaps(&shape, dx, dy), !hits.is_empty());
+                assert_eq!(canvas.overlapping_points(&shape, dx, dy), hits);
+            }
+        }
+        // Letters don’t count.
+        assert!(!canvas.overlaps(&shape, 20, 0));
+    }
+
+    fn cell(canvas: &Canvas, x: u32, y: u32) -> (u8, char, bool, PixelColor) {
+        let pos = ((x / 2) as u16, (y / 4) as u16);
+        canvas
+            .chars
+            .get(&pos)
+            .cloned()
+            .unwrap_or((0, ' ', false, PixelColor::White))
+    }
+
+    #[test]
+    fn operators() {
+        let mut a = Canvas::new(10, 8);
+        a.line_colored(0, 0, 19, 7, PixelColor::Red);
+        a.set_char(0, 4, 'a');
+        a.set_char(16, 0, 'c');
+        let mut b = Canvas::new(30, 4);
+        b.line_colored(0, 7, 19, 0, PixelColor::Blue);
+        b.set_colored(25, 1, PixelColor::Blue);
+        b.line(0, 0, 3, 1);
+        b.set_char(0, 4, 'b');
+        b.set_char(16, 0, 'c');
+        let (pa, pb) = (pixels(&a), pixels(&b));
+
+        let union = &a | &b;
+        let mut both: Vec<_> = pa.iter().chain(&pb).cloned().collect();
+        both.sort_by_key(|&(x, y)| (y, x));
+        both.dedup();
+        assert_eq!(pixels(&union), both);
+        assert_eq!(pixels(&union), pixels(&(&b | &a)));
+        // The left-hand side keeps its colours and letters.
+        assert_eq!(cell(&union, 10, 3).3, PixelColor::Red);
+        assert_eq!(cell(&union, 0, 4).1, 'a');
+        assert_eq!(cell(&union, 25, 1).3, PixelColor::Blue);
+
+        let intersection = &a & &b;
+        let common: Vec<_> = pa.iter().filter(|p| pb.contains(p)).cloned().collect();
+        assert!(!common.is_empty());
+        assert_eq!(pixels(&intersection), common);
+        // Only letters in both are kept.
+        assert_eq!(cell(&intersection, 0, 4).1, ' ');
+        assert_eq!(cell(&intersection, 16, 0).1, 'c');
+
+        let sum = &a + &b;
+        assert_eq!(pixels(&sum), both);
+        // The right-hand side is drawn on top.
+        assert_eq!(cell(&sum, 10, 3).3, PixelColor::Blue);
+        assert_eq!(cell(&sum, 0, 4).1, 'b');
+        assert_eq!(cell(&sum, 5, 1).3, PixelColor::Red);
+
+        // Every form of each operator agrees.
+        let mut assigned = a.clone();
+        assigned |= &b;
+        assert!(assigned == union && a.clone() | b.clone() == union);
+        let mut assigned = a.clone();
+        assigned &= b.clone();
+        assert!(assigned == intersection && a.clone() & &b == intersection);
+        let mut assigned = a.clone();
+        assigned += &b;
+        assert!(assigned == sum && a.clone() + b.clone() == sum);
+    }
+
+    /// Checks `tile` against setting each pixel of the region from the pattern one at a time.
+    fn check_tile(pattern: &Canvas, x: u32, y: u32, width: u32, height: u32) {
+        let (cols, rows) = pattern.cell_bounds();
+        let (pw, ph) = (cols as u32 * 2, rows as u32 * 4);
+        let mut tiled = Canvas::new(0, 0);
+        tiled.tile(pattern, x, y, width, height);
+        let mut expected = Vec::new();
+        for j in 0..height {
+            for i in 0..width {
+                if pattern.get(i % pw, j % ph) {
+                    expected.push((x + i, y + j));
+                }
+            }
+        }
+        expected.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(pixels(&tiled), expected);
+    }
+
+    #[test]
+    fn tile_at_any_offset() {
+        // Pixels either side of a cell boundary, and a pattern larger than its contents.
+        let mut pattern = Canvas::new(6, 8);
+        pattern.set(1, 3);
+        pattern.set(2, 4);
+        pattern.set_colored(5, 6, PixelColor::Green);
+        for &(x, y) in &[(0, 0), (3, 5), (1, 2), (4, 4)] {
+            check_tile(&pattern, x, y, 20, 19);
+        }
+        let mut tiled = Canvas::new(0, 0);
+        tiled.tile(&pattern, 3, 5, 20, 19);
+        assert_eq!(
+            cell(&tiled, 8, 11),
+            (0b0100_0000, ' ', true, PixelColor::Green)
+        );
+        assert!(!cell(&tiled, 4, 8).2);
+
+        // Contents beyond the pattern’s own size widen each repeat.
+        let mut pattern = Canvas::new(2, 4);
+        pattern.set(0, 0);
+        pattern.set(3, 5);
+        check_tile(&pattern, 1, 3, 17, 21);
+
+        let mut tiled = Canvas::new(0, 0);
+        tiled.tile(&Canvas::new(0, 0), 0, 0, 10, 10);
+        assert!(pixels(&tiled).is_empty());
+    }
+
+    #[test]
+    fn rotated_moves_every_pixel() {
+        let mut canvas = Canvas::new(10, 8);
+        canvas.line(0, 0, 9, 2);
+        canvas.set_colored(3, 6, PixelColor::Red);
+        // Beyond the `Canvas`’s height, so the drawing sets where its bottom edge is.
+        canvas.set(1, 13);
+        canvas.set_char(6, 0, 'x');
+
+        let bottom = 15;
+        let mut expected = Canvas::new(8, 8);
+        for (x, y) in pixels(&canvas) {
+            if (x, y) == (3, 6) {
+                expected.set_colored(bottom - y, x, PixelColor::Red);
+            } else {
+                expected.set(bottom - y, x);
+            }
+        }
+        expected.set_char(bottom - 3, 6, 'x');
+        let rotated = canvas.rotated();
+        assert_eq!(pixels(&rotated), pixels(&expected));
+        assert_eq!(cell(&rotated, bottom - 6, 3).3, PixelColor::Red);
+        assert_eq!(canvas.rows_rotated(), expected.rows());
+    }
+
+    #[test]
+    fn fit_to_shrinks_large_drawings() {
+        let mut canvas = Canvas::new(20, 8);
+        canvas.line(0, 0, 19, 7);
+        canvas.set_char(4, 4, 'a');
+        assert!(canvas.fit_to(20, 8, 0.5) == canvas);
+        assert!(canvas.fit_to(100, 100, 0.5) == canvas);
+
+        let mut canvas = Canvas::new(200, 100);
+        canvas.line(0, 0, 199, 99);
+        let fitted = canvas.fit_to(50, 50, 0.0);
+        let (cols, rows) = fitted.cell_bounds();
+        assert!(cols * 2 <= 50 && rows * 4 <= 52);
+        let points = pixels(&fitted);
+        assert!(points.iter().all(|&(x, y)| x < 50 && y < 25));
+        assert!(points.contains(&(0, 0)) && points.contains(&(49, 24)));
+
+        // Drawing outside the `Canvas`’s dimensions is fitted too, and with no coverage
+        // needed every row of a steep line keeps a pixel.
+        let mut canvas = Canvas::new(0, 0);
+        canvas.line(0, 0, 99, 399);
+        let points = pixels(&canvas.fit_to(20, 20, 0.0));
+        assert!(points.iter().all(|&(x, y)| x < 5 && y < 20));
+        assert!((0..20).all(|j| points.iter().any(|&(_, y)| y == j)));
+    }
+
+    #[test]
+    fn rainbow_lines() {
+        let mut canvas = Canvas::new(0, 0);
+        canvas.rainbow(30.0);
+        // Each cell takes the hue of the later of its two pixels, at 30° and then every 60°.
+        canvas.line_colored(0, 0, 10, 0, PixelColor::Blue);
+        assert_eq!(cell(&canvas, 0, 0).3, rgb(255, 128, 0));
+        assert_eq!(cell(&canvas, 2, 0).3, rgb(128, 255, 0));
+        assert_eq!(cell(&canvas, 4, 0).3, rgb(0, 255, 128));
+        assert_eq!(cell(&canvas, 10, 0).3, rgb(255, 0, 255));
+        // The next lines carry on from 300°, where the last one left off, and wrap past 360°.
+        canvas.line(0, 8, 0, 8);
+        assert_eq!(cell(&canvas, 0, 8).3, rgb(255, 0, 255));
+        canvas.line(0, 12, 0, 14);
+        assert_eq!(cell(&canvas, 0, 12).3, rgb(255, 0, 0));
+
+        canvas.no_rainbow();
+        canvas.line_colored(0, 16, 10, 16, PixelColor::Blue);
+        assert!((0..6).all(|col| cell(&canvas, col * 2, 16).3 == PixelColor::Blue));
+        canvas.line(0, 20, 10, 20);
+        assert!((0..6).all(|col| !cell(&canvas, col * 2, 20).2));
+    }
+
+    #[test]
+    fn color_modes() {
+        let first_row = |canvas: &Canvas| canvas.rows()[0].trim_end().to_string();
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_colored(0, 0, PixelColor::Red);
+        canvas.set(2, 0);
+        canvas.set_color_mode(ColorMode::Always);
+        assert_eq!(first_row(&canvas), "\x1b[31m⠁\x1b[0m⠁");
+        canvas.set_color_mode(ColorMode::Never);
+        assert_eq!(first_row(&canvas), "⠁⠁");
+
+        // `Auto` leaves it to `colored`, which is what `NO_COLOR` turns off. Output isn’t a
+        // terminal under test either, so overriding it to off can’t upset other tests.
+        canvas.set_color_mode(ColorMode::Auto);
+        colored::control::set_override(false);
+        let row = first_row(&canvas);
+        colored::control::unset_override();
+        assert_eq!(row, "⠁⠁");
+
+        canvas.set_color_mode(ColorMode::Always);
+        canvas.set_colored(2, 0, PixelColor::White);
+        canvas.set_theme(light_theme);
+        assert_eq!(first_row(&canvas), "\x1b[31m⠁\x1b[0m\x1b[30m⠁\x1b[0m");
+        canvas.clear_theme();
+        assert_eq!(first_row(&canvas), "\x1b[31m⠁\x1b[0m\x1b[37m⠁\x1b[0m");
+    }
+
+    #[test]
+    fn color_reset_at_end() {
+        let mut canvas = Canvas::new(6, 8);
+        canvas.set_color_mode(ColorMode::Always);
+        canvas.set_colored(0, 0, PixelColor::Red);
+        canvas.set_colored(2, 0, PixelColor::Red);
+        canvas.set(4, 0);
+        canvas.set_colored(0, 4, PixelColor::Blue);
+        assert_eq!(
+            canvas.rows(),
+            vec![
+                "\x1b[31m⠁\x1b[0m\x1b[31m⠁\x1b[0m⠁ ",
+                "\x1b[34m⠁\x1b[0m   ",
+                "    "
+            ]
+        );
+
+        // Colours only switch when they change, and carry across rows and blank spaces until
+        // the one reset at the end.
+        canvas.set_color_reset(ColorReset::End);
+        assert_eq!(
+            canvas.rows(),
+            vec!["\x1b[31m⠁⠁\x1b[0m⠁ ", "\x1b[34m⠁   ", "    \x1b[0m"]
+        );
+        assert_eq!(canvas.frame(), canvas.rows().join("\n"));
+        // Rows drawn on their own each reset what they leave set.
+        let rows: Vec<_> = canvas.rows_iter().collect();
+        assert_eq!(
+            rows,
+            vec!["\x1b[31m⠁⠁\x1b[0m⠁ ", "\x1b[34m⠁   \x1b[0m", "    "]
+        );
+
+        canvas.set_color_mode(ColorMode::Never);
+        assert_eq!(canvas.rows(), vec!["⠁⠁⠁ ", "⠁   ", "    "]);
+    }
+
+    #[test]
+    fn color_depth_from_environment() {
+        use super::ColorDepth::*;
+        assert_eq!(
+            color_depth_for("xterm-256color", "truecolor", true),
+            Some(TrueColor)
+        );
+        assert_eq!(color_depth_for("xterm", "24BIT", true), Some(TrueColor));
+        assert_eq!(color_depth_for("xterm-256color", "", true), Some(Ansi256));
+        assert_eq!(
+            color_depth_for("screen-256color", "yes", true),
+            Some(Ansi256)
+        );
+        assert_eq!(color_depth_for("xterm", "", true), Some(Ansi16));
+        assert_eq!(color_depth_for("", "", true), Some(Ansi16));
+        // `dumb` terminals and `colored` saying no (for `NO_COLOR`, say) win over everything.
+        assert_eq!(color_depth_for("dumb", "truecolor", true), None);
+        assert_eq!(color_depth_for("xterm-256color", "truecolor", false), None);
+    }
+
+    #[test]
+    fn wrapping_edges() {
+        // `get` wraps too, so only look within the edges, and check nothing is stored past them.
+        let inside = |canvas: &Canvas| {
+            assert_eq!(canvas.cell_bounds(), (10, 2));
+            let points = pixels(canvas);
+            points
+                .into_iter()
+                .filter(|&(x, y)| x < 20 && y < 8)
+                .collect::<Vec<_>>()
+        };
+        let mut canvas = Canvas::new(20, 8);
+        assert_eq!(canvas.wrapping(), None);
+        canvas.set_wrapping(true);
+        assert_eq!(canvas.wrapping(), Some((20, 8)));
+        canvas.set(23, 9);
+        canvas.line(18, 4, 22, 4);
+        assert_eq!(
+            inside(&canvas),
+            vec![(3, 1), (0, 4), (1, 4), (2, 4), (18, 4), (19, 4)]
+        );
+        assert!(canvas.get(43, 17));
+        canvas.unset(3, 17);
+        assert!(!canvas.get(3, 1));
+
+        // An edge with no size doesn’t wrap.
+        let mut canvas = Canvas::new(0, 8);
+        canvas.set_wrapping(true);
+        canvas.set(30, 9);
+        assert!(canvas.get(30, 1));
+        assert_eq!(canvas.cell_bounds(), (16, 2));
+
+        // A turtle walking off an edge comes back on the opposite side, even past zero.
+        let mut canvas = Canvas::new(20, 8);
+        canvas.set_wrapping(true);
+        let mut turtle = Turtle::from_canvas(2.0, 2.0, canvas);
+        turtle.teleport(-3.0, 2.0);
+        assert_eq!((turtle.x, turtle.y), (17.0, 2.0));
+        assert_eq!(
+            inside(&turtle.cvs),
+            vec![(0, 2), (1, 2), (2, 2), (17, 2), (18, 2), (19, 2)]
+        );
+        turtle.teleport(17.0, 10.0);
+        assert_eq!((turtle.x, turtle.y), (17.0, 2.0));
+        assert!((0..8).all(|y| turtle.cvs.get(17, y)));
+    }
+
+    #[test]
+    fn frame_chunks_join_into_frame() {
+        let mut canvas = plain_canvas();
+        canvas.line(0, 0, 9, 30);
+        canvas.set_char(4, 12, 'a');
+        let frame = canvas.frame();
+        for rows in 0..10 {
+            let chunks: Vec<_> = canvas.frame_chunks(rows).collect();
+            assert_eq!(chunks.concat(), frame, "{} rows at a time", rows);
+            let step = rows.max(1) as usize;
+            assert_eq!(chunks.len(), 8usize.div_ceil(step));
+            for (i, chunk) in chunks.iter().enumerate() {
+                let lines: Vec<_> = frame.lines().skip(i * step).take(step).collect();
+                assert_eq!(chunk.trim_end_matches('\n'), lines.join("\n"));
+            }
+            let mut out = Vec::new();
+            canvas.write_frame(&mut out, rows).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), frame);
+        }
+
+        // Each piece resets the colour it leaves set.
+        let mut canvas = Canvas::new(2, 8);
+        canvas.set_color_mode(ColorMode::Always);
+        canvas.set_color_reset(ColorReset::End);
+        canvas.set_colored(0, 0, PixelColor::Red);
+        canvas.set_colored(0, 4, PixelColor::Red);
+        let chunks: Vec<_> = canvas.frame_chunks(2).collect();
+        assert_eq!(chunks, vec!["\x1b[31m⠁ \n⠁ \x1b[0m\n", "  "]);
+    }
+
+    #[test]
+    fn glyph_tables() {
+        assert_eq!(braille(0), '⠀');
+        assert_eq!(braille(0x01), '⠁');
+        assert_eq!(braille(0xff), '⣿');
+        assert_eq!(ascii_glyph(0), ' ');
+        assert_eq!(ascii_glyph(0x40), '.');
+        assert_eq!(ascii_glyph(0x09), ':');
+        assert_eq!(ascii_glyph(0x7f), '#');
+        assert_eq!(ascii_glyph(0xff), '@');
+
+        let mut canvas = plain_canvas();
+        canvas.set(0, 0);
+        canvas.line(2, 0, 3, 3);
+        canvas.set_char(4, 0, 'a');
+        canvas.set_colored(6, 0, PixelColor::Red);
+        let braille_rows = canvas.rows();
+        canvas.set_glyphs(ascii_glyph);
+        assert_eq!(canvas.rows(), vec![".=a.  ", "      ", "      "]);
+        canvas.set_color_mode(ColorMode::Always);
+        assert_eq!(canvas.rows()[0], ".=a\x1b[31m.\x1b[0m  ");
+
+        // The dots are passed on as laid out in `PIXEL_MAP`.
+        canvas.set_color_mode(ColorMode::Never);
+        canvas.set_glyphs(|dots| if dots & 0x01 != 0 { 'T' } else { 'x' });
+        canvas.set(9, 3);
+        assert_eq!(canvas.rows()[0], "TTaTx ");
+        canvas.set_glyphs(braille);
+        canvas.unset(9, 3);
+        assert_eq!(canvas.rows(), braille_rows);
+    }
+
+    #[test]
+    fn text_extents() {
+        assert_eq!(Canvas::text_extent(""), (0, 0));
+        assert_eq!(Canvas::text_extent("abc"), (6, 4));
+        // Wide letters take two columns, and combining marks none.
+        assert_eq!(Canvas::text_extent("日本"), (8, 4));
+        assert_eq!(Canvas::text_extent("a日b"), (8, 4));
+        assert_eq!(Canvas::text_extent("e\u{301}"), (2, 4));
+
+        assert_eq!(Canvas::text_extent_wrapped("one two three", 14), (14, 8));
+        assert_eq!(Canvas::text_extent_wrapped("one\ntwo", 100), (6, 8));
+        assert_eq!(Canvas::text_extent_wrapped("日本語", 8), (8, 8));
+        // A single character too wide for a line still gets one to itself.
+        assert_eq!(Canvas::text_extent_wrapped("日本", 2), (4, 8));
+
+        let mut canvas = plain_canvas();
+        canvas.text_wrapped(0, 0, 14, "one two three");
+        canvas.text(0, 8, 100, "日本a");
+        let rows: Vec<_> = canvas
+            .rows()
+            .iter()
+            .map(|r| r.trim_end().to_owned())
+            .collect();
+        assert_eq!(rows, vec!["one two", "three", "日本a"]);
+    }
+
+    #[test]
+    fn nearest_indexed_colours() {
+        assert_eq!(nearest_indexed(0, 0, 0), 16);
+        assert_eq!(nearest_indexed(255, 0, 0), 196);
+        assert_eq!(nearest_indexed(0, 255, 0), 46);
+        assert_eq!(nearest_indexed(0, 0, 255), 21);
+        assert_eq!(nearest_indexed(255, 255, 255), 231);
+        // Greys come from the ramp rather than the cube.
+        assert_eq!(nearest_indexed(128, 128, 128), 244);
+        assert_eq!(nearest_indexed(8, 8, 8), 232);
+        // Either side of the first two steps of the cube, at 0, 95 and 135.
+        assert_eq!(nearest_indexed(47, 255, 0), 46);
+        assert_eq!(nearest_indexed(48, 255, 0), 82);
+        assert_eq!(nearest_indexed(114, 255, 0), 82);
+        assert_eq!(nearest_indexed(116, 255, 0), 118);
+    }
+
+    #[test]
+    fn nearest_ansi_colours() {
+        assert_eq!(nearest_ansi(rgb(0, 0, 0)), PixelColor::Black);
+        assert_eq!(nearest_ansi(rgb(255, 0, 0)), PixelColor::BrightRed);
+        assert_eq!(nearest_ansi(rgb(0, 205, 0)), PixelColor::Green);
+        assert_eq!(nearest_ansi(rgb(0, 0, 238)), PixelColor::Blue);
+        assert_eq!(nearest_ansi(rgb(255, 255, 255)), PixelColor::BrightWhite);
+        assert_eq!(nearest_ansi(rgb(128, 128, 128)), PixelColor::BrightBlack);
+        // Halfway between red, at 205, and bright red, at 255.
+        assert_eq!(nearest_ansi(rgb(230, 0, 0)), PixelColor::Red);
+        assert_eq!(nearest_ansi(rgb(231, 0, 0)), PixelColor::BrightRed);
+        assert_eq!(nearest_ansi(PixelColor::Cyan), PixelColor::Cyan);
+    }
+
+    #[test]
+    fn symmetry_mirrors_about_its_centre() {
+        let axes = [
+            (Symmetry::new(10, 6).horizontal(), (3, 2), (3, 10)),
+            (Symmetry::new(10, 6).vertical(), (3, 2), (17, 2)),
+            (Symmetry::new(10, 6).diagonal(), (5, 8), (12, 1)),
+        ];
+        for &(symmetry, (x, y), (mx, my)) in &axes {
+            let mut canvas = Canvas::new(0, 0);
+            canvas.set_symmetry(symmetry);
+            canvas.set(x, y);
+            let mut expected = Canvas::new(0, 0);
+            expected.set(x, y);
+            expected.set(mx, my);
+            assert!(canvas == expected, "{:?}", symmetry);
+
+            // Erasing only touches the pixel given.
+            canvas.unset(x, y);
+            assert!(!canvas.get(x, y) && canvas.get(mx, my));
+            canvas.toggle(x, y);
+            assert!(canvas.get(x, y) && canvas.get(mx, my));
+            canvas.unset_line(mx, my, mx, my);
+            assert!(canvas.get(x, y) && !canvas.get(mx, my));
+        }
+
+        let mut canvas = Canvas::new(0, 0);
+        canvas.set_symmetry(Symmetry::new(1, 1).horizontal().vertical());
+        canvas.set(0, 0);
+        assert!(canvas.get(0, 0) && canvas.get(2, 0) && canvas.get(0, 2) && canvas.get(2, 2));
+        canvas.set(5, 0);
+        // The mirror images at x = -3 are skipped.
+        assert!(canvas.get(5, 0) && canvas.get(5, 2));
+    }
+
+    #[test]
+    fn lines_match_line() {
+        let segments = [
+            ((0, 0), (13, 7)),
+            ((13, 7), (2, 11)),
+            ((5, 0), (5, 9)),
+            ((1, 3), (1, 3)),
+        ];
+        for &color in &[None, Some(PixelColor::Magenta)] {
+            let (mut merged, mut single) = (Canvas::new(0, 0), Canvas::new(0, 0));
+            // Earlier drawing in the same cells is kept, and recoloured, alike.
+            merged.set_colored(1, 1, PixelColor::Red);
+            single.set_colored(1, 1, PixelColor::Red);
+            merged.set_char(20, 8, 'x');
+            single.set_char(20, 8, 'x');
+            match color {
+                Some(color) => merged.lines_colored(&segments, color),
+                None => merged.lines(&segments),
+            }
+            for &((x1, y1), (x2, y2)) in &segments {
+                match color {
+                    Some(color) => single.line_colored(x1, y1, x2, y2, color),
+                    None => single.line(x1, y1, x2, y2),
+                }
+            }
+            assert!(merged == single);
+            assert_eq!(merged.frame(), single.frame());
+        }
+    }
+
+    #[test]
+    fn cycling_leaves_recoloured_cells_alone() {
+        let mut plain = indexed_canvas();
+        plain.set_colored(0, 0, PixelColor::Red);
+        plain.cycle_palette();
+
+        let redraws: [fn(&mut Canvas); 4] = [
+            |c| c.set_colored(0, 0, PixelColor::Red),
+            |c| c.lines_colored(&[((0, 0), (0, 0))], PixelColor::Red),
+            |c| c.map_pixels(|_, _, set, _| (set, Some(PixelColor::Red))),
+            |c| {
+                c.retain(|_, _, _| false);
+                c.set_colored(0, 0, PixelColor::Red);
+            },
+        ];
+        for redraw in &redraws {
+            let mut canvas = indexed_canvas();
+            canvas.set_indexed(0, 0, 0);
+            redraw(&mut canvas);
+            canvas.cycle_palette();
+            assert_eq!(canvas.frame(), plain.frame());
+        }
+
+        let mut canvas = indexed_canvas();
+        canvas.set_indexed(0, 0, 0);
+        canvas.cycle_palette();
+        assert!(canvas.frame() != plain.frame());
+    }
+
+    #[test]
+    fn turtle_runs_scripts() {
+        let mut turtle = Turtle::new(0.0, 0.0);
+        turtle.run("F10 r90 F4 U B2 D C#ff0000 L90 c").unwrap();
+        let mut expected = Turtle::new(0.0, 0.0);
+        expected.forward(10.0);
+        expected.right(90.0);
+        expected.forward(4.0);
+        expected.up();
+        expected.back(2.0);
+        expected.down();
+        expected.color(PixelColor::TrueColor { r: 255, g: 0, b: 0 });
+        expected.left(90.0);
+        expected.clean_brush();
+        assert_eq!(turtle.frame(), expected.frame());
+        assert_eq!((turtle.x, turtle.y), (expected.x, expected.y));
+        assert!(!turtle.use_color);
+    }
+
+    #[test]
+    fn turtle_radians() {
+        use std::f32::consts::PI;
+
+        let mut degrees = Turtle::new(20.0, 20.0);
+        degrees.right(90.0);
+        degrees.forward(10.0);
+        degrees.left(45.0);
+        degrees.forward(8.0);
+        degrees.teleport_polar(6.0, 180.0);
+        degrees.forward_to_angle(4.0, -90.0);
+
+        let mut radians = Turtle::new(20.0, 20.0);
+        radians.use_radians();
+        radians.right(PI / 2.0);
+        radians.forward(10.0);
+        radians.left(PI / 4.0);
+        radians.forward(8.0);
+        radians.teleport_polar(6.0, PI);
+        radians.forward_to_angle(4.0, -PI / 2.0);
+
+        assert!((degrees.x - radians.x).abs() < 1e-4 && (degrees.y - radians.y).abs() < 1e-4);
+        assert!((radians.rotation - PI / 4.0).abs() < 1e-6);
+        assert_eq!(degrees.frame(), radians.frame());
+
+        radians.use_degrees();
+        assert!((radians.rotation - 45.0).abs() < 1e-4);
+        radians.use_radians();
+        assert!((radians.rotation - PI / 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn turtle_rejects_invalid_scripts() {
+        for script in &["F10 X5", "F10 Cnotacolour", "Finf", "F10 RNaN", "U3", "F"] {
+            let mut turtle = Turtle::new(0.0, 0.0);
+            assert!(turtle.run(script).is_err(), "{}", script);
+            assert_eq!(turtle.frame(), Canvas::new(0, 0).frame(), "{}", script);
+            assert_eq!((turtle.x, turtle.y), (0.0, 0.0));
+        }
+        let error = Turtle::new(0.0, 0.0).run("F1 Finfinity").unwrap_err();
+        assert_eq!(error.to_string(), "invalid turtle command: `Finfinity`");
+    }
+}
@@ -0,0 +1,55 @@
+//! Conversion between terminal cells and pixels, for handling the mouse.
+//!
+//! Terminals report mouse events by the column and row of the character under the pointer.
+//! Each character shows two by four pixels of a canvas, so a cell maps to a small block of
+//! pixels, and a pixel to exactly one cell.
+
+/// Where a canvas is drawn on the terminal.
+///
+/// A `Placement` records the terminal cell that the top-left character of the drawing is
+/// printed in, and which pixel of the canvas that character starts at (for drawings of part of
+/// a canvas, such as with `Canvas::frame_in`). Cells are counted from zero, as reported by
+/// crossterm; subtract one from the positions reported by termion first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Placement {
+    /// The column and row of the terminal cell holding the top-left character of the drawing.
+    pub origin: (u32, u32),
+    /// The pixel of the canvas that the top-left character of the drawing starts at.
+    pub offset: (u32, u32),
+}
+
+impl Placement {
+    /// Creates a `Placement` for a whole canvas drawn with its top-left character at the given
+    /// column and row of the terminal.
+    pub fn at(col: u32, row: u32) -> Placement {
+        Placement {
+            origin: (col, row),
+            offset: (0, 0),
+        }
+    }
+
+    /// Sets the pixel of the canvas at the top-left of the drawing, rounded down to the start
+    /// of its character, and returns the `Placement` for use again.
+    pub fn offset(mut self, x: u32, y: u32) -> Placement {
+        self.offset = (x / 2 * 2, y / 4 * 4);
+        self
+    }
+
+    /// Returns the top-left pixel of the canvas shown in the terminal cell at `(col, row)`, or
+    /// `None` if the cell is above or to the left of the drawing.
+    ///
+    /// The cell shows the two by four pixels starting at that one.
+    pub fn to_pixel(&self, col: u32, row: u32) -> Option<(u32, u32)> {
+        let col = col.checked_sub(self.origin.0)?;
+        let row = row.checked_sub(self.origin.1)?;
+        Some((self.offset.0 + col * 2, self.offset.1 + row * 4))
+    }
+
+    /// Returns the terminal cell, as `(col, row)`, that the pixel at `(x, y)` of the canvas is
+    /// shown in, or `None` if the pixel is above or to the left of the drawing.
+    pub fn to_cell(&self, x: u32, y: u32) -> Option<(u32, u32)> {
+        let x = x.checked_sub(self.offset.0)?;
+        let y = y.checked_sub(self.offset.1)?;
+        Some((self.origin.0 + x / 2, self.origin.1 + y / 4))
+    }
+}
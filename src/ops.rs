@@ -0,0 +1,109 @@
+//! Operators for composing canvases.
+//!
+//! `a | b` takes the union of two canvases, `a & b` their intersection and `a + b` draws `b` on
+//! top of `a`. Each works on whole characters at a time, and the result keeps the drawing modes
+//! of the left-hand side.
+
+use std::cmp;
+use std::ops::{Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
+use super::Canvas;
+
+impl Canvas {
+    /// Adds the pixels and letters of `other` to the `Canvas`, keeping the existing letters and
+    /// colours where the two overlap.
+    fn union(&mut self, other: &Canvas) {
+        for (&pos, &cell) in &other.chars {
+            let (dots, _, colored, color) = cell;
+            let a = self.chars.entry(pos, cell);
+            match *a {
+                (0, ' ', _, _) => *a = cell,
+                (0, _, _, _) => {}
+                _ if dots == 0 => {}
+                _ => {
+                    a.0 |= dots;
+                    if !a.2 && colored {
+                        a.2 = true;
+                        a.3 = color;
+                    }
+                }
+            }
+        }
+        self.grow(other);
+    }
+
+    /// Removes every pixel of the `Canvas` that isn’t also set in `other`, along with any
+    /// letters that `other` doesn’t have in the same place.
+    fn intersect(&mut self, other: &Canvas) {
+        self.chars
+            .retain(|pos, a| match (*a, other.chars.get(pos)) {
+                ((0, c, _, _), Some(&(0, d, _, _))) => c == d && c != ' ',
+                ((0, _, _, _), _) | (_, None) => false,
+                (_, Some(&(dots, _, _, _))) => {
+                    a.0 &= dots;
+                    a.0 != 0
+                }
+            });
+        self.width = cmp::min(self.width, other.width);
+        self.height = cmp::min(self.height, other.height);
+    }
+
+    /// Draws `other` on top of the `Canvas`, like `blit` at the origin.
+    fn overlay(&mut self, other: &Canvas) {
+        self.blit(other, 0, 0);
+        self.grow(other);
+    }
+
+    fn grow(&mut self, other: &Canvas) {
+        self.width = cmp::max(self.width, other.width);
+        self.height = cmp::max(self.height, other.height);
+    }
+}
+
+macro_rules! canvas_op {
+    ($op:ident, $method:ident, $assign:ident, $assign_method:ident, $merge:ident) => {
+        impl<'a> $assign<&'a Canvas> for Canvas {
+            fn $assign_method(&mut self, other: &Canvas) {
+                self.$merge(other);
+            }
+        }
+
+        impl $assign for Canvas {
+            fn $assign_method(&mut self, other: Canvas) {
+                self.$merge(&other);
+            }
+        }
+
+        impl<'a> $op<&'a Canvas> for Canvas {
+            type Output = Canvas;
+
+            fn $method(mut self, other: &Canvas) -> Canvas {
+                self.$merge(other);
+                self
+            }
+        }
+
+        impl $op for Canvas {
+            type Output = Canvas;
+
+            fn $method(mut self, other: Canvas) -> Canvas {
+                self.$merge(&other);
+                self
+            }
+        }
+
+        impl<'a, 'b> $op<&'b Canvas> for &'a Canvas {
+            type Output = Canvas;
+
+            fn $method(self, other: &Canvas) -> Canvas {
+                let mut canvas = self.clone();
+                canvas.$merge(other);
+                canvas
+            }
+        }
+    };
+}
+
+canvas_op!(BitOr, bitor, BitOrAssign, bitor_assign, union);
+canvas_op!(BitAnd, bitand, BitAndAssign, bitand_assign, intersect);
+canvas_op!(Add, add, AddAssign, add_assign, overlay);
@@ -0,0 +1,71 @@
+//! Named, ordered sets of colours.
+
+use super::PixelColor;
+
+/// An ordered list of named colours, for colouring several things consistently.
+///
+/// Colours are looked up by position with `get`, which wraps around to the start once it
+/// reaches the end, so any number of series can be coloured from the same `Palette`. The
+/// default `Palette` holds six distinct colours that read well on a dark background.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: Vec<(String, PixelColor)>,
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette::new()
+            .color("blue", PixelColor::Blue)
+            .color("green", PixelColor::Green)
+            .color("yellow", PixelColor::Yellow)
+            .color("magenta", PixelColor::Magenta)
+            .color("cyan", PixelColor::Cyan)
+            .color("red", PixelColor::Red)
+    }
+}
+
+impl Palette {
+    /// Creates a new, empty `Palette`.
+    pub fn new() -> Palette {
+        Palette { colors: Vec::new() }
+    }
+
+    /// Adds a colour to the end of the `Palette` under the given name, and returns the
+    /// `Palette` for use again.
+    pub fn color(mut self, name: &str, color: PixelColor) -> Palette {
+        self.colors.push((name.to_owned(), color));
+        self
+    }
+
+    /// Returns the colour at `index`, counting round again from the start past the end of the
+    /// `Palette`. An empty `Palette` only has white.
+    pub fn get(&self, index: usize) -> PixelColor {
+        if self.colors.is_empty() {
+            return PixelColor::White;
+        }
+        self.colors[index % self.colors.len()].1
+    }
+
+    /// Returns the colour with the given name, if there is one.
+    pub fn named(&self, name: &str) -> Option<PixelColor> {
+        self.colors.iter().find(|c| c.0 == name).map(|c| c.1)
+    }
+
+    /// Returns the name of the colour at `index`, counting round like `get`.
+    pub fn name(&self, index: usize) -> Option<&str> {
+        if self.colors.is_empty() {
+            return None;
+        }
+        Some(&self.colors[index % self.colors.len()].0)
+    }
+
+    /// Returns the number of colours in the `Palette`.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns whether the `Palette` has no colours.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
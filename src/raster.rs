@@ -0,0 +1,238 @@
+//! Rasterization of shapes into pixels.
+//!
+//! These are the algorithms a `Canvas` draws its shapes with, available on their own so that
+//! the pixels of a shape can be put to other uses, such as hit-testing, animating something
+//! along a path or colouring each pixel differently.
+
+use std::cmp;
+
+use super::{degrees_to_radians, Segment};
+
+/// The default curve tolerance of a `Canvas`, in pixels.
+pub const DEFAULT_TOLERANCE: f32 = 0.5;
+
+/// Returns the pixels along a line from `(x1, y1)` to `(x2, y2)`, in order, as drawn by
+/// `Canvas::line`.
+pub fn line_points(x1: u32, y1: u32, x2: u32, y2: u32) -> impl Iterator<Item = (u32, u32)> {
+    let xdiff = cmp::max(x1, x2) - cmp::min(x1, x2);
+    let ydiff = cmp::max(y1, y2) - cmp::min(y1, y2);
+    let xdir = if x1 <= x2 { 1 } else { -1 };
+    let ydir = if y1 <= y2 { 1 } else { -1 };
+
+    let r = cmp::max(xdiff, ydiff);
+
+    (0..=r).map(move |i| {
+        let mut x = x1 as i32;
+        let mut y = y1 as i32;
+
+        if ydiff != 0 {
+            y += ((i * ydiff) / r) as i32 * ydir;
+        }
+        if xdiff != 0 {
+            x += ((i * xdiff) / r) as i32 * xdir;
+        }
+
+        (x as u32, y as u32)
+    })
+}
+
+/// Returns the pixels of the ellipse centred on `(xm, ym)` with a horizontal radius of `a` and
+/// a vertical radius of `b`, as drawn by `Canvas::ellipse_center` with square pixels.
+///
+/// Pixels at negative coordinates are left out, and some pixels may appear more than once.
+pub fn ellipse_points(xm: u32, ym: u32, a: u32, b: u32) -> impl Iterator<Item = (u32, u32)> {
+    // Signed coordinates, as the ellipse can stick out past the top and left.
+    let mut points = Vec::new();
+    if a == b {
+        circle(xm as i64, ym as i64, a as i64, &mut points);
+    } else {
+        ellipse(xm as i64, ym as i64, a as i64, b as i64, &mut points);
+    }
+    points.into_iter().filter_map(unsigned)
+}
+
+/// Returns the pixels of part of the ellipse centred on `(xm, ym)` with radii `a` and `b`,
+/// from `start_deg` to `end_deg` degrees, in order, as drawn by `Canvas::ellipse_arc` with
+/// square pixels and the default curve tolerance.
+///
+/// Pixels at negative coordinates are left out, and some pixels may appear more than once.
+pub fn arc_points(
+    xm: u32,
+    ym: u32,
+    a: u32,
+    b: u32,
+    start_deg: f32,
+    end_deg: f32,
+) -> impl Iterator<Item = (u32, u32)> {
+    let segments = arc_segments(xm, ym, a, b, start_deg, end_deg, DEFAULT_TOLERANCE);
+    segments
+        .into_iter()
+        .flat_map(|((x1, y1), (x2, y2))| line_points(x1, y1, x2, y2))
+}
+
+/// Returns the pixels inside the polygon with the given corners, including its outline, as
+/// drawn by `Canvas::fill_polygon`.
+///
+/// The inside is given row by row from the top, followed by the outline.
+pub fn polygon_points(points: &[(u32, u32)]) -> impl Iterator<Item = (u32, u32)> {
+    let mut pixels = polygon_inside(points);
+    for (i, &(x1, y1)) in points.iter().enumerate() {
+        let (x2, y2) = points[(i + 1) % points.len()];
+        pixels.extend(line_points(x1, y1, x2, y2));
+    }
+    pixels.into_iter()
+}
+
+/// Returns the pixels whose centres are inside the polygon with the given corners, by the
+/// even-odd rule, row by row from the top.
+pub fn polygon_inside(points: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut pixels = Vec::new();
+    let (top, bottom) = match (
+        points.iter().map(|p| p.1).min(),
+        points.iter().map(|p| p.1).max(),
+    ) {
+        (Some(top), Some(bottom)) => (top, bottom),
+        _ => return pixels,
+    };
+    let mut crossings = Vec::new();
+    for y in top..=bottom {
+        // Each row is sampled through the centres of its pixels.
+        let sy = y as f64 + 0.5;
+        crossings.clear();
+        for (i, &(x1, y1)) in points.iter().enumerate() {
+            let (x2, y2) = points[(i + 1) % points.len()];
+            let (x1, y1, x2, y2) = (
+                x1 as f64 + 0.5,
+                y1 as f64 + 0.5,
+                x2 as f64 + 0.5,
+                y2 as f64 + 0.5,
+            );
+            if (y1 <= sy) != (y2 <= sy) {
+                crossings.push(x1 + (sy - y1) * (x2 - x1) / (y2 - y1));
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+        for pair in crossings.chunks(2) {
+            if let [start, end] = *pair {
+                let start = (start - 0.5).ceil().max(0.0) as u32;
+                let end = (end - 0.5).floor();
+                if end >= start as f64 {
+                    pixels.extend((start..=end as u32).map(|x| (x, y)));
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Returns the straight segments an arc is flattened into, so that none strays more than
+/// `tolerance` pixels from it. Segments with an end at negative coordinates are left out.
+pub fn arc_segments(
+    xm: u32,
+    ym: u32,
+    a: u32,
+    b: u32,
+    start_deg: f32,
+    end_deg: f32,
+    tolerance: f32,
+) -> Vec<Segment> {
+    let mut sweep = end_deg - start_deg;
+    if sweep < 0.0 {
+        sweep = sweep.rem_euclid(360.0);
+    }
+    let sweep = degrees_to_radians(sweep.min(360.0));
+    let start = degrees_to_radians(start_deg);
+    let steps = curve_steps(cmp::max(a, b) as f32, sweep, tolerance);
+    let point = |t: f32| {
+        let x = xm as f32 + a as f32 * t.cos();
+        let y = ym as f32 + b as f32 * t.sin();
+        (x.round() as i64, y.round() as i64)
+    };
+    let mut segments = Vec::new();
+    let mut prev = point(start);
+    for i in 1..=steps {
+        let next = point(start + sweep * i as f32 / steps as f32);
+        if let (Some(from), Some(to)) = (unsigned(prev), unsigned(next)) {
+            segments.push((from, to));
+        }
+        prev = next;
+    }
+    segments
+}
+
+/// Returns the number of segments to draw an arc of `sweep` radians with the given radius in,
+/// so that none strays more than `tolerance` pixels from the arc.
+fn curve_steps(radius: f32, sweep: f32, tolerance: f32) -> u32 {
+    let tolerance = tolerance.max(0.01);
+    if radius <= tolerance {
+        return 1;
+    }
+    // The furthest a chord strays from its arc is `radius * (1 - cos(angle / 2))`.
+    let angle = 2.0 * (1.0 - tolerance / radius).acos();
+    (sweep / angle).ceil().clamp(1.0, 1e6) as u32
+}
+
+/// Draws an ellipse with the usual error-stepping algorithm.
+fn ellipse(xm: i64, ym: i64, a: i64, b: i64, points: &mut Vec<(i64, i64)>) {
+    let (mut x, mut y) = (-a, 0);
+    let mut err = x * (2 * b * b + x) + b * b;
+    loop {
+        points.push((xm - x, ym + y));
+        points.push((xm + x, ym + y));
+        points.push((xm + x, ym - y));
+        points.push((xm - x, ym - y));
+        let e2 = 2 * err;
+        if e2 >= (x * 2 + 1) * b * b {
+            x += 1;
+            err += (x * 2 + 1) * b * b;
+        }
+        if e2 <= (y * 2 + 1) * a * a {
+            y += 1;
+            err += (y * 2 + 1) * a * a;
+        }
+        if x > 0 {
+            break;
+        }
+    }
+    // Flat ellipses stop early, so finish off their tips.
+    while y < b {
+        y += 1;
+        points.push((xm, ym + y));
+        points.push((xm, ym - y));
+    }
+}
+
+/// Draws a circle with the midpoint circle algorithm, which is faster than drawing it as an
+/// ellipse and doesn’t miss any pixels of small circles.
+fn circle(xm: i64, ym: i64, r: i64, points: &mut Vec<(i64, i64)>) {
+    let (mut x, mut y, mut d) = (r, 0, 1 - r);
+    while x >= y {
+        for &(dx, dy) in &[
+            (x, y),
+            (y, x),
+            (-y, x),
+            (-x, y),
+            (-x, -y),
+            (-y, -x),
+            (y, -x),
+            (x, -y),
+        ] {
+            points.push((xm + dx, ym + dy));
+        }
+        y += 1;
+        if d < 0 {
+            d += 2 * y + 1;
+        } else {
+            x -= 1;
+            d += 2 * (y - x) + 1;
+        }
+    }
+}
+
+fn unsigned((x, y): (i64, i64)) -> Option<(u32, u32)> {
+    if x >= 0 && y >= 0 && x <= u32::MAX as i64 && y <= u32::MAX as i64 {
+        Some((x as u32, y as u32))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,836 @@
+//! Recording and replaying of drawing commands.
+//!
+//! A `Canvas` that is recording keeps a log of every drawing call made on it. Each `Command`
+//! can be written out as a single line of text and parsed back again, so a log can be stored
+//! and later replayed onto another `Canvas`, or any other `PixelCanvas`, possibly at a
+//! different scale.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{parse_color, PixelCanvas, PixelColor};
+
+/// A single drawing command recorded by a `Canvas`.
+///
+/// Only calls that draw from coordinates and values are recorded. Calls that copy or rework
+/// existing pixels (`blit`, `blit_with_opacity`, `tile`, `map_pixels` and `retain`) and those
+/// that change how later calls draw (`set_symmetry`, `rainbow`, `set_wrapping` and
+/// `set_pixel_aspect`) are left out, so a log only reproduces a drawing made without them. The
+/// commands that modes affect are recorded as they were called, before mirroring, wrapping or
+/// colouring.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// `Canvas::clear`.
+    Clear,
+    /// `Canvas::set`.
+    Set { x: u32, y: u32 },
+    /// `Canvas::set_colored`.
+    SetColored { x: u32, y: u32, color: PixelColor },
+    /// `Canvas::set_char`.
+    SetChar { x: u32, y: u32, c: char },
+    /// `Canvas::text`.
+    Text {
+        x: u32,
+        y: u32,
+        max_width: u32,
+        text: String,
+    },
+    /// `Canvas::unset`.
+    Unset { x: u32, y: u32 },
+    /// `Canvas::toggle`.
+    Toggle { x: u32, y: u32 },
+    /// `Canvas::line`.
+    Line { x1: u32, y1: u32, x2: u32, y2: u32 },
+    /// `Canvas::line_colored`.
+    LineColored {
+        x1: u32,
+        y1: u32,
+        x2: u32,
+        y2: u32,
+        color: PixelColor,
+    },
+    /// `Canvas::unset_line`.
+    UnsetLine { x1: u32, y1: u32, x2: u32, y2: u32 },
+    /// `Canvas::ellipse_center`.
+    Ellipse { xm: u32, ym: u32, a: u32, b: u32 },
+    /// `Canvas::ellipse_center_colored`.
+    EllipseColored {
+        xm: u32,
+        ym: u32,
+        a: u32,
+        b: u32,
+        color: PixelColor,
+    },
+    /// `Canvas::ellipse_arc`.
+    Arc {
+        xm: u32,
+        ym: u32,
+        a: u32,
+        b: u32,
+        start_deg: f32,
+        end_deg: f32,
+    },
+    /// `Canvas::fill_polygon`.
+    FillPolygon { points: Vec<(u32, u32)> },
+    /// `Canvas::fill_polygon_colored`.
+    FillPolygonColored {
+        points: Vec<(u32, u32)>,
+        color: PixelColor,
+    },
+    /// `Canvas::fill_rect_density`.
+    FillRectDensity {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        density: f32,
+    },
+    /// `Canvas::fill_ellipse_density`.
+    FillEllipseDensity {
+        xm: u32,
+        ym: u32,
+        a: u32,
+        b: u32,
+        density: f32,
+    },
+    /// `Canvas::set_rows_from_bits`.
+    SetRowsFromBits {
+        x: u32,
+        y: u32,
+        width: u32,
+        data: Vec<u8>,
+    },
+    /// `Canvas::stamp`.
+    Stamp {
+        x: u32,
+        y: u32,
+        width: u32,
+        rows: Vec<bool>,
+    },
+}
+
+impl Command {
+    /// Applies the command to a canvas, multiplying every coordinate by `scale`. Bitmaps, as
+    /// drawn by `Canvas::stamp` and `Canvas::set_rows_from_bits`, are moved but keep their
+    /// size.
+    ///
+    /// Commands that the canvas has no way of drawing, such as letters on a canvas that only
+    /// has pixels, are drawn as well as it can (see `PixelCanvas`).
+    pub fn apply<C: PixelCanvas + ?Sized>(&self, canvas: &mut C, scale: f32) {
+        let s = |v: u32| (v as f32 * scale).round() as u32;
+        match *self {
+            Command::Clear => canvas.clear(),
+            Command::Set { x, y } => canvas.set(s(x), s(y)),
+            Command::SetColored { x, y, color } => canvas.set_colored(s(x), s(y), color),
+            Command::SetChar { x, y, c } => canvas.set_char(s(x), s(y), c),
+            Command::Text {
+                x,
+                y,
+                max_width,
+                ref text,
+            } => canvas.text(s(x), s(y), s(max_width), text),
+            Command::Unset { x, y } => canvas.unset(s(x), s(y)),
+            Command::Toggle { x, y } => canvas.toggle(s(x), s(y)),
+            Command::Line { x1, y1, x2, y2 } => canvas.line(s(x1), s(y1), s(x2), s(y2)),
+            Command::LineColored {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => canvas.line_colored(s(x1), s(y1), s(x2), s(y2), color),
+            Command::UnsetLine { x1, y1, x2, y2 } => canvas.unset_line(s(x1), s(y1), s(x2), s(y2)),
+            Command::Ellipse { xm, ym, a, b } => canvas.ellipse_center(s(xm), s(ym), s(a), s(b)),
+            Command::EllipseColored {
+                xm,
+                ym,
+                a,
+                b,
+                color,
+            } => canvas.ellipse_center_colored(s(xm), s(ym), s(a), s(b), color),
+            Command::Arc {
+                xm,
+                ym,
+                a,
+                b,
+                start_deg,
+                end_deg,
+            } => canvas.ellipse_arc(s(xm), s(ym), s(a), s(b), start_deg, end_deg),
+            Command::FillPolygon { ref points } => {
+                let points: Vec<(u32, u32)> = points.iter().map(|&(x, y)| (s(x), s(y))).collect();
+                canvas.fill_polygon(&points);
+            }
+            Command::FillPolygonColored { ref points, color } => {
+                let points: Vec<(u32, u32)> = points.iter().map(|&(x, y)| (s(x), s(y))).collect();
+                canvas.fill_polygon_colored(&points, color);
+            }
+            Command::FillRectDensity {
+                x,
+                y,
+                width,
+                height,
+                density,
+            } => canvas.fill_rect_density(s(x), s(y), s(width), s(height), density),
+            Command::FillEllipseDensity {
+                xm,
+                ym,
+                a,
+                b,
+                density,
+            } => canvas.fill_ellipse_density(s(xm), s(ym), s(a), s(b), density),
+            Command::SetRowsFromBits {
+                x,
+                y,
+                width,
+                ref data,
+            } => canvas.set_rows_from_bits(s(x), s(y), width, data),
+            Command::Stamp {
+                x,
+                y,
+                width,
+                ref rows,
+            } => canvas.stamp(s(x), s(y), width, rows),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Command::Clear => write!(f, "clear"),
+            Command::Set { x, y } => write!(f, "set {} {}", x, y),
+            Command::SetColored { x, y, color } => {
+                write!(f, "set_colored {} {} {}", x, y, color_name(color))
+            }
+            Command::SetChar { x, y, c } => {
+                write!(
+                    f,
+                    "set_char {} {} {}",
+                    x,
+                    y,
+                    escape(c.encode_utf8(&mut [0; 4]))
+                )
+            }
+            Command::Text {
+                x,
+                y,
+                max_width,
+                ref text,
+            } => write!(f, "text {} {} {} {}", x, y, max_width, escape(text)),
+            Command::Unset { x, y } => write!(f, "unset {} {}", x, y),
+            Command::Toggle { x, y } => write!(f, "toggle {} {}", x, y),
+            Command::Line { x1, y1, x2, y2 } => write!(f, "line {} {} {} {}", x1, y1, x2, y2),
+            Command::LineColored {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => write!(
+                f,
+                "line_colored {} {} {} {} {}",
+                x1,
+                y1,
+                x2,
+                y2,
+                color_name(color)
+            ),
+            Command::UnsetLine { x1, y1, x2, y2 } => {
+                write!(f, "unset_line {} {} {} {}", x1, y1, x2, y2)
+            }
+            Command::Ellipse { xm, ym, a, b } => write!(f, "ellipse {} {} {} {}", xm, ym, a, b),
+            Command::EllipseColored {
+                xm,
+                ym,
+                a,
+                b,
+                color,
+            } => write!(
+                f,
+                "ellipse_colored {} {} {} {} {}",
+                xm,
+                ym,
+                a,
+                b,
+                color_name(color)
+            ),
+            Command::Arc {
+                xm,
+                ym,
+                a,
+                b,
+                start_deg,
+                end_deg,
+            } => write!(f, "arc {} {} {} {} {} {}", xm, ym, a, b, start_deg, end_deg),
+            Command::FillPolygon { ref points } => {
+                write!(f, "fill_polygon")?;
+                write_points(f, points)
+            }
+            Command::FillPolygonColored { ref points, color } => {
+                write!(f, "fill_polygon_colored {}", color_name(color))?;
+                write_points(f, points)
+            }
+            Command::FillRectDensity {
+                x,
+                y,
+                width,
+                height,
+                density,
+            } => write!(
+                f,
+                "fill_rect_density {} {} {} {} {}",
+                x, y, width, height, density
+            ),
+            Command::FillEllipseDensity {
+                xm,
+                ym,
+                a,
+                b,
+                density,
+            } => write!(
+                f,
+                "fill_ellipse_density {} {} {} {} {}",
+                xm, ym, a, b, density
+            ),
+            Command::SetRowsFromBits {
+                x,
+                y,
+                width,
+                ref data,
+            } => {
+                write!(f, "set_rows_from_bits {} {} {}", x, y, width)?;
+                if !data.is_empty() {
+                    write!(f, " ")?;
+                    for byte in data {
+                        write!(f, "{:02x}", byte)?;
+                    }
+                }
+                Ok(())
+            }
+            Command::Stamp {
+                x,
+                y,
+                width,
+                ref rows,
+            } => {
+                write!(f, "stamp {} {} {}", x, y, width)?;
+                if !rows.is_empty() {
+                    let bits: String = rows.iter().map(|&on| if on { '1' } else { '0' }).collect();
+                    write!(f, " {}", bits)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An error returned when a line of text can’t be parsed as a `Command`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseCommandError {
+    line: String,
+}
+
+impl fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid drawing command: `{}`", self.line)
+    }
+}
+
+impl Error for ParseCommandError {}
+
+impl FromStr for Command {
+    type Err = ParseCommandError;
+
+    fn from_str(line: &str) -> Result<Command, ParseCommandError> {
+        let err = || ParseCommandError {
+            line: line.to_owned(),
+        };
+        let (name, rest) = split_word(line);
+
+        // `set_char` and `text` take their last argument verbatim, spaces included, apart from
+        // the escapes written by `escape`.
+        if name == "set_char" || name == "text" {
+            let argc = if name == "text" { 3 } else { 2 };
+            let mut args = Vec::with_capacity(argc);
+            let mut rest = rest;
+            for _ in 0..argc {
+                let (arg, tail) = split_word(rest);
+                args.push(arg.parse::<u32>().map_err(|_| err())?);
+                rest = tail;
+            }
+            return if name == "text" {
+                Ok(Command::Text {
+                    x: args[0],
+                    y: args[1],
+                    max_width: args[2],
+                    text: unescape(rest).ok_or_else(err)?,
+                })
+            } else {
+                let c = unescape(rest).ok_or_else(err)?;
+                let mut chars = c.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(Command::SetChar {
+                        x: args[0],
+                        y: args[1],
+                        c,
+                    }),
+                    _ => Err(err()),
+                }
+            };
+        }
+
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let num = |i: usize| -> Result<u32, ParseCommandError> {
+            words.get(i).and_then(|w| w.parse().ok()).ok_or_else(err)
+        };
+        let float = |i: usize| -> Result<f32, ParseCommandError> {
+            words
+                .get(i)
+                .and_then(|w| w.parse().ok())
+                .filter(|a: &f32| a.is_finite())
+                .ok_or_else(err)
+        };
+        let color = |i: usize| -> Result<PixelColor, ParseCommandError> {
+            words
+                .get(i)
+                .and_then(|w| parse_color(w).ok())
+                .ok_or_else(err)
+        };
+        // A polygon’s corners follow its other arguments, as many as there are.
+        if name == "fill_polygon" || name == "fill_polygon_colored" {
+            let colored = name == "fill_polygon_colored";
+            let corners = words.get(colored as usize..).ok_or_else(err)?;
+            if corners.len() % 2 != 0 {
+                return Err(err());
+            }
+            let coords = corners
+                .iter()
+                .map(|w| w.parse::<u32>().map_err(|_| err()))
+                .collect::<Result<Vec<u32>, _>>()?;
+            let points = coords.chunks(2).map(|p| (p[0], p[1])).collect();
+            return Ok(if colored {
+                Command::FillPolygonColored {
+                    points,
+                    color: color(0)?,
+                }
+            } else {
+                Command::FillPolygon { points }
+            });
+        }
+
+        // A bitmap is written after its position and width as a string of bits, or of bytes in
+        // hexadecimal for a packed one, which is left out if it has no pixels.
+        if name == "set_rows_from_bits" {
+            let data = match words.get(3) {
+                Some(hex) if hex.len() % 2 == 0 && hex.is_ascii() => (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| err()))
+                    .collect::<Result<Vec<u8>, _>>()?,
+                Some(_) => return Err(err()),
+                None => Vec::new(),
+            };
+            if words.len() < 3 || words.len() > 4 {
+                return Err(err());
+            }
+            return Ok(Command::SetRowsFromBits {
+                x: num(0)?,
+                y: num(1)?,
+                width: num(2)?,
+                data,
+            });
+        }
+        if name == "stamp" {
+            let rows = match words.get(3) {
+                Some(bits) => bits
+                    .chars()
+                    .map(|c| match c {
+                        '0' => Ok(false),
+                        '1' => Ok(true),
+                        _ => Err(err()),
+                    })
+                    .collect::<Result<Vec<bool>, _>>()?,
+                None => Vec::new(),
+            };
+            if words.len() < 3 || words.len() > 4 {
+                return Err(err());
+            }
+            return Ok(Command::Stamp {
+                x: num(0)?,
+                y: num(1)?,
+                width: num(2)?,
+                rows,
+            });
+        }
+
+        let (command, argc) = match name {
+            "clear" => (Command::Clear, 0),
+            "set" => (
+                Command::Set {
+                    x: num(0)?,
+                    y: num(1)?,
+                },
+                2,
+            ),
+            "set_colored" => (
+                Command::SetColored {
+                    x: num(0)?,
+                    y: num(1)?,
+                    color: color(2)?,
+                },
+                3,
+            ),
+            "unset" => (
+                Command::Unset {
+                    x: num(0)?,
+                    y: num(1)?,
+                },
+                2,
+            ),
+            "toggle" => (
+                Command::Toggle {
+                    x: num(0)?,
+                    y: num(1)?,
+                },
+                2,
+            ),
+            "line" => (
+                Command::Line {
+                    x1: num(0)?,
+                    y1: num(1)?,
+                    x2: num(2)?,
+                    y2: num(3)?,
+                },
+                4,
+            ),
+            "line_colored" => (
+                Command::LineColored {
+                    x1: num(0)?,
+                    y1: num(1)?,
+                    x2: num(2)?,
+                    y2: num(3)?,
+                    color: color(4)?,
+                },
+                5,
+            ),
+            "unset_line" => (
+                Command::UnsetLine {
+                    x1: num(0)?,
+                    y1: num(1)?,
+                    x2: num(2)?,
+                    y2: num(3)?,
+                },
+                4,
+            ),
+            "ellipse" => (
+                Command::Ellipse {
+                    xm: num(0)?,
+                    ym: num(1)?,
+                    a: num(2)?,
+                    b: num(3)?,
+                },
+                4,
+            ),
+            "ellipse_colored" => (
+                Command::EllipseColored {
+                    xm: num(0)?,
+                    ym: num(1)?,
+                    a: num(2)?,
+                    b: num(3)?,
+                    color: color(4)?,
+                },
+                5,
+            ),
+            "arc" => (
+                Command::Arc {
+                    xm: num(0)?,
+                    ym: num(1)?,
+                    a: num(2)?,
+                    b: num(3)?,
+                    start_deg: float(4)?,
+                    end_deg: float(5)?,
+                },
+                6,
+            ),
+            "fill_rect_density" => (
+                Command::FillRectDensity {
+                    x: num(0)?,
+                    y: num(1)?,
+                    width: num(2)?,
+                    height: num(3)?,
+                    density: float(4)?,
+                },
+                5,
+            ),
+            "fill_ellipse_density" => (
+                Command::FillEllipseDensity {
+                    xm: num(0)?,
+                    ym: num(1)?,
+                    a: num(2)?,
+                    b: num(3)?,
+                    density: float(4)?,
+                },
+                5,
+            ),
+            _ => return Err(err()),
+        };
+        if words.len() != argc {
+            return Err(err());
+        }
+        Ok(command)
+    }
+}
+
+fn write_points(f: &mut fmt::Formatter, points: &[(u32, u32)]) -> fmt::Result {
+    for &(x, y) in points {
+        write!(f, " {} {}", x, y)?;
+    }
+    Ok(())
+}
+
+/// Escapes backslashes and line breaks in `text`, so that it fits on one line.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Undoes `escape`, returning `None` if `text` has a backslash not followed by one of the
+/// escapes it writes.
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            _ => c,
+        });
+    }
+    Some(unescaped)
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    match s.find(' ') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, ""),
+    }
+}
+
+fn color_name(color: PixelColor) -> String {
+    match color {
+        PixelColor::Black => "black".to_owned(),
+        PixelColor::Red => "red".to_owned(),
+        PixelColor::Green => "green".to_owned(),
+        PixelColor::Yellow => "yellow".to_owned(),
+        PixelColor::Blue => "blue".to_owned(),
+        PixelColor::Magenta => "magenta".to_owned(),
+        PixelColor::Cyan => "cyan".to_owned(),
+        PixelColor::White => "white".to_owned(),
+        PixelColor::BrightBlack => "bright_black".to_owned(),
+        PixelColor::BrightRed => "bright_red".to_owned(),
+        PixelColor::BrightGreen => "bright_green".to_owned(),
+        PixelColor::BrightYellow => "bright_yellow".to_owned(),
+        PixelColor::BrightBlue => "bright_blue".to_owned(),
+        PixelColor::BrightMagenta => "bright_magenta".to_owned(),
+        PixelColor::BrightCyan => "bright_cyan".to_owned(),
+        PixelColor::BrightWhite => "bright_white".to_owned(),
+        PixelColor::TrueColor { r, g, b } => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Command;
+    use {Canvas, PixelColor};
+
+    fn round_trip(command: Command) {
+        let line = command.to_string();
+        assert!(!line.contains('\n'), "{:?}", line);
+        assert_eq!(line.parse::<Command>(), Ok(command), "{:?}", line);
+    }
+
+    #[test]
+    fn commands_round_trip() {
+        round_trip(Command::Clear);
+        round_trip(Command::Set { x: 3, y: 4 });
+        round_trip(Command::SetColored {
+            x: 0,
+            y: 9,
+            color: PixelColor::BrightMagenta,
+        });
+        round_trip(Command::SetColored {
+            x: 1,
+            y: 2,
+            color: PixelColor::TrueColor { r: 255, g: 8, b: 0 },
+        });
+        round_trip(Command::Unset { x: 5, y: 6 });
+        round_trip(Command::Toggle { x: 7, y: 8 });
+        round_trip(Command::Line {
+            x1: 0,
+            y1: 1,
+            x2: 20,
+            y2: 30,
+        });
+        round_trip(Command::LineColored {
+            x1: 4,
+            y1: 3,
+            x2: 2,
+            y2: 1,
+            color: PixelColor::Red,
+        });
+        round_trip(Command::UnsetLine {
+            x1: 9,
+            y1: 9,
+            x2: 0,
+            y2: 0,
+        });
+        round_trip(Command::Ellipse {
+            xm: 10,
+            ym: 12,
+            a: 5,
+            b: 0,
+        });
+        round_trip(Command::EllipseColored {
+            xm: 1,
+            ym: 2,
+            a: 3,
+            b: 4,
+            color: PixelColor::Cyan,
+        });
+        round_trip(Command::Arc {
+            xm: 10,
+            ym: 12,
+            a: 5,
+            b: 7,
+            start_deg: -45.5,
+            end_deg: 1.0 / 3.0,
+        });
+        round_trip(Command::FillRectDensity {
+            x: 1,
+            y: 2,
+            width: 30,
+            height: 40,
+            density: 0.25,
+        });
+        round_trip(Command::FillEllipseDensity {
+            xm: 20,
+            ym: 20,
+            a: 8,
+            b: 4,
+            density: 0.7,
+        });
+        round_trip(Command::SetRowsFromBits {
+            x: 4,
+            y: 8,
+            width: 12,
+            data: vec![0x00, 0xff, 0x5a, 0xa5, 0x0f, 0xf0],
+        });
+        round_trip(Command::SetRowsFromBits {
+            x: 0,
+            y: 0,
+            width: 8,
+            data: vec![],
+        });
+        round_trip(Command::Stamp {
+            x: 3,
+            y: 1,
+            width: 3,
+            rows: vec![true, false, true, false, true, false, true],
+        });
+        round_trip(Command::Stamp {
+            x: 0,
+            y: 0,
+            width: 0,
+            rows: vec![],
+        });
+        round_trip(Command::FillPolygon { points: vec![] });
+        round_trip(Command::FillPolygon {
+            points: vec![(0, 0), (10, 2), (4, 8)],
+        });
+        round_trip(Command::FillPolygonColored {
+            points: vec![(1, 1), (9, 9), (1, 9)],
+            color: PixelColor::Green,
+        });
+    }
+
+    #[test]
+    fn text_round_trips() {
+        for text in &[
+            "",
+            " ",
+            "two  spaces ",
+            "a\nb",
+            "back\\slash\\n",
+            "\r\n",
+            "日本",
+        ] {
+            round_trip(Command::Text {
+                x: 1,
+                y: 2,
+                max_width: 80,
+                text: (*text).to_owned(),
+            });
+        }
+        for &c in &[' ', '\n', '\\', 'x', '字'] {
+            round_trip(Command::SetChar { x: 0, y: 0, c });
+        }
+    }
+
+    #[test]
+    fn replay_draws_the_same() {
+        let mut canvas = Canvas::new(40, 40);
+        canvas.start_recording();
+        canvas.line(0, 0, 30, 9);
+        canvas.ellipse_center(20, 20, 10, 6);
+        canvas.ellipse_arc(20, 20, 15, 15, 30.0, 200.0);
+        canvas.text(2, 30, 40, "hi");
+        canvas.fill_rect_density(0, 0, 12, 12, 0.3);
+        canvas.fill_ellipse_density(30, 10, 6, 5, 0.6);
+        canvas.stamp(5, 33, 3, &[true, true, false, false, true, true]);
+        canvas.set_rows_from_bits(20, 0, 10, &[0xf0, 0x40, 0x0f, 0xc0]);
+        canvas.fill_polygon_colored(&[(30, 30), (38, 32), (33, 39)], PixelColor::Blue);
+        let lines: Vec<String> = canvas.commands().iter().map(|c| c.to_string()).collect();
+        let commands: Vec<Command> = lines.iter().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(commands.len(), 9);
+
+        let mut copy = Canvas::new(40, 40);
+        copy.replay(&commands, 1.0);
+        assert_eq!(copy.frame(), canvas.frame());
+    }
+
+    #[test]
+    fn invalid_commands() {
+        for line in &[
+            "",
+            "set 1",
+            "set 1 2 3",
+            "line 1 2 3",
+            "nope",
+            "text 1 2 3 \\q",
+            "arc 1 2 3 4 5 inf",
+            "fill_polygon 1 2 3",
+            "fill_polygon_colored 1 2",
+            "fill_polygon_colored",
+            "stamp 1 2",
+            "set_rows_from_bits 1 2 8 abc",
+            "set_rows_from_bits 1 2 8 zz",
+            "stamp 1 2 3 0120",
+            "stamp 1 2 3 01 01",
+        ] {
+            assert!(line.parse::<Command>().is_err(), "{:?}", line);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Retained-mode drawing.
+//!
+//! A `Scene` keeps the shapes drawn in it, so that one can be moved, recoloured or removed
+//! without having to draw everything else again by hand.
+
+use super::{Canvas, PixelColor, Point};
+
+/// A shape that can be added to a `Scene`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A straight line between two points.
+    Line(Point, Point),
+    /// A circle around a centre point, with a radius in pixels.
+    Circle(Point, u32),
+    /// Lines joining a series of points in order, from the last back to the first if `closed`.
+    Path { points: Vec<Point>, closed: bool },
+    /// Text with its top-left corner at a point, as drawn by `Canvas::text`. Text is never
+    /// coloured.
+    Text(Point, String),
+}
+
+impl Shape {
+    /// Moves the shape `dx` pixels right and `dy` pixels down, stopping at the top and left
+    /// edges of the canvas.
+    fn translate(&mut self, dx: i32, dy: i32) {
+        let shift = |p: &mut Point| {
+            p.0 = (p.0 as i64 + dx as i64).clamp(0, u32::MAX as i64) as u32;
+            p.1 = (p.1 as i64 + dy as i64).clamp(0, u32::MAX as i64) as u32;
+        };
+        match *self {
+            Shape::Line(ref mut a, ref mut b) => {
+                shift(a);
+                shift(b);
+            }
+            Shape::Circle(ref mut centre, _) | Shape::Text(ref mut centre, _) => shift(centre),
+            Shape::Path { ref mut points, .. } => points.iter_mut().for_each(shift),
+        }
+    }
+
+    fn draw(&self, canvas: &mut Canvas, color: Option<PixelColor>) {
+        let line = |canvas: &mut Canvas, (x1, y1): Point, (x2, y2): Point| match color {
+            Some(color) => canvas.line_colored(x1, y1, x2, y2, color),
+            None => canvas.line(x1, y1, x2, y2),
+        };
+        match *self {
+            Shape::Line(a, b) => line(canvas, a, b),
+            Shape::Circle((x, y), r) => match color {
+                Some(color) => canvas.ellipse_center_colored(x, y, r, r, color),
+                None => canvas.ellipse_center(x, y, r, r),
+            },
+            Shape::Path { ref points, closed } => {
+                for pair in points.windows(2) {
+                    line(canvas, pair[0], pair[1]);
+                }
+                match (points.first(), points.last()) {
+                    (Some(&first), Some(&last)) if closed && points.len() > 2 => {
+                        line(canvas, last, first)
+                    }
+                    (Some(&first), _) if points.len() == 1 => line(canvas, first, first),
+                    _ => {}
+                }
+            }
+            Shape::Text((x, y), ref text) => canvas.text(x, y, u32::MAX, text),
+        }
+    }
+}
+
+/// An identifier for a shape in a `Scene`, returned when the shape is added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeId(u64);
+
+/// A collection of shapes that can be changed after they are added and drawn again at any time.
+///
+/// Shapes are drawn in the order they were added, so later shapes are drawn over earlier ones.
+#[derive(Clone, Debug)]
+pub struct Scene {
+    width: u32,
+    height: u32,
+    shapes: Vec<(ShapeId, Shape, Option<PixelColor>)>,
+    next: u64,
+}
+
+impl Scene {
+    /// Creates a new, empty `Scene` drawn onto canvases of `width` by `height` pixels.
+    pub fn new(width: u32, height: u32) -> Scene {
+        Scene {
+            width,
+            height,
+            shapes: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Adds a shape on top of the others, and returns its identifier.
+    pub fn add(&mut self, shape: Shape) -> ShapeId {
+        self.insert(shape, None)
+    }
+
+    /// Adds a shape on top of the others drawn in the given colour, and returns its
+    /// identifier.
+    pub fn add_colored(&mut self, shape: Shape, color: PixelColor) -> ShapeId {
+        self.insert(shape, Some(color))
+    }
+
+    /// Returns the shape with the given identifier, if it hasn’t been removed.
+    pub fn get(&self, id: ShapeId) -> Option<&Shape> {
+        self.find(id).map(|i| &self.shapes[i].1)
+    }
+
+    /// Replaces the shape with the given identifier, keeping its colour and its place among
+    /// the others. Returns whether there was such a shape.
+    pub fn replace(&mut self, id: ShapeId, shape: Shape) -> bool {
+        self.find(id).map(|i| self.shapes[i].1 = shape).is_some()
+    }
+
+    /// Moves the shape with the given identifier `dx` pixels right and `dy` pixels down.
+    /// Returns whether there was such a shape.
+    pub fn move_by(&mut self, id: ShapeId, dx: i32, dy: i32) -> bool {
+        self.find(id)
+            .map(|i| self.shapes[i].1.translate(dx, dy))
+            .is_some()
+    }
+
+    /// Changes the colour of the shape with the given identifier, or removes it if `color` is
+    /// `None`. Returns whether there was such a shape.
+    pub fn set_color(&mut self, id: ShapeId, color: Option<PixelColor>) -> bool {
+        self.find(id).map(|i| self.shapes[i].2 = color).is_some()
+    }
+
+    /// Removes the shape with the given identifier from the `Scene` and returns it.
+    pub fn remove(&mut self, id: ShapeId) -> Option<Shape> {
+        self.find(id).map(|i| self.shapes.remove(i).1)
+    }
+
+    /// Removes every shape from the `Scene`.
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    /// Draws every shape of the `Scene` onto a new `Canvas` and returns it.
+    pub fn render(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        for &(_, ref shape, color) in &self.shapes {
+            shape.draw(&mut canvas, color);
+        }
+        canvas
+    }
+
+    fn insert(&mut self, shape: Shape, color: Option<PixelColor>) -> ShapeId {
+        let id = ShapeId(self.next);
+        self.next += 1;
+        self.shapes.push((id, shape, color));
+        id
+    }
+
+    fn find(&self, id: ShapeId) -> Option<usize> {
+        // Identifiers only ever grow, so the shapes are kept sorted by them.
+        self.shapes.binary_search_by_key(&id, |s| s.0).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scene, Shape};
+    use {Canvas, PixelColor};
+
+    fn pixels(canvas: &Canvas) -> Vec<(u32, u32)> {
+        let mut points = Vec::new();
+        for y in 0..40 {
+            for x in 0..40 {
+                if canvas.get(x, y) {
+                    points.push((x, y));
+                }
+            }
+        }
+        points
+    }
+
+    fn color_at(canvas: &Canvas, x: u32, y: u32) -> Option<PixelColor> {
+        let cell = canvas.chars.get(&((x / 2) as u16, (y / 4) as u16));
+        cell.filter(|cell| cell.2).map(|cell| cell.3)
+    }
+
+    #[test]
+    fn render_draws_shapes_in_order() {
+        let mut scene = Scene::new(40, 40);
+        scene.add(Shape::Line((0, 0), (9, 0)));
+        scene.add_colored(Shape::Circle((20, 20), 5), PixelColor::Red);
+        scene.add(Shape::Path {
+            points: vec![(0, 30), (10, 30), (10, 36)],
+            closed: true,
+        });
+        scene.add(Shape::Text((30, 0), "hi".to_owned()));
+
+        let mut expected = Canvas::new(40, 40);
+        expected.line(0, 0, 9, 0);
+        expected.ellipse_center_colored(20, 20, 5, 5, PixelColor::Red);
+        expected.line(0, 30, 10, 30);
+        expected.line(10, 30, 10, 36);
+        expected.line(10, 36, 0, 30);
+        expected.text(30, 0, u32::MAX, "hi");
+        assert!(scene.render() == expected);
+    }
+
+    #[test]
+    fn colored_circles_match_plain_ones() {
+        let mut plain = Scene::new(40, 40);
+        plain.add(Shape::Circle((20, 20), 9));
+        let mut colored = Scene::new(40, 40);
+        colored.add_colored(Shape::Circle((20, 20), 9), PixelColor::Blue);
+        assert_eq!(pixels(&colored.render()), pixels(&plain.render()));
+
+        // Both take the pixel aspect into account, which makes the circle twice as tall.
+        let (mut plain, mut colored) = (Canvas::new(40, 40), Canvas::new(40, 40));
+        plain.set_pixel_aspect(2.0);
+        colored.set_pixel_aspect(2.0);
+        plain.ellipse_center(20, 20, 9, 9);
+        colored.ellipse_center_colored(20, 20, 9, 9, PixelColor::Blue);
+        assert_eq!(pixels(&colored), pixels(&plain));
+        assert!(plain.get(29, 20) && plain.get(20, 2) && !plain.get(20, 11));
+    }
+
+    #[test]
+    fn render_after_changes() {
+        let mut scene = Scene::new(40, 40);
+        let line = scene.add(Shape::Line((0, 0), (9, 0)));
+        let dot = scene.add_colored(
+            Shape::Path {
+                points: vec![(4, 0)],
+                closed: false,
+            },
+            PixelColor::Green,
+        );
+        let text = scene.add(Shape::Text((0, 8), "a".to_owned()));
+        assert_eq!(color_at(&scene.render(), 4, 0), Some(PixelColor::Green));
+
+        // Moving a shape redraws it in its new place, and clamps it at the edges.
+        assert!(scene.move_by(line, 3, 12));
+        assert!(scene.move_by(text, -5, 4));
+        let canvas = scene.render();
+        let mut moved: Vec<_> = (3..13).map(|x| (x, 12)).collect();
+        moved.insert(0, (4, 0));
+        assert_eq!(pixels(&canvas), moved);
+        assert_eq!(scene.get(text), Some(&Shape::Text((0, 12), "a".to_owned())));
+
+        // Recolouring keeps the shape’s place, so it is still drawn over the ones before it.
+        assert!(scene.set_color(line, Some(PixelColor::Red)));
+        assert!(scene.move_by(dot, 0, 12));
+        assert_eq!(color_at(&scene.render(), 4, 12), Some(PixelColor::Green));
+        assert_eq!(color_at(&scene.render(), 8, 12), Some(PixelColor::Red));
+        assert!(scene.set_color(dot, None));
+        assert_eq!(color_at(&scene.render(), 4, 12), None);
+
+        assert_eq!(scene.remove(line), Some(Shape::Line((3, 12), (12, 12))));
+        assert_eq!(scene.remove(line), None);
+        assert!(!scene.move_by(line, 1, 1) && !scene.set_color(line, None));
+        let mut expected = Canvas::new(40, 40);
+        expected.set(4, 12);
+        expected.text(0, 12, u32::MAX, "a");
+        assert!(scene.render() == expected);
+    }
+}
@@ -0,0 +1,139 @@
+//! A canvas that can be drawn on from several threads at once.
+
+use std::sync::{Mutex, MutexGuard};
+
+use super::{line_points, Canvas, PixelColor};
+
+/// The number of shards the cells of a `SharedCanvas` are split between.
+const SHARDS: usize = 16;
+
+/// A canvas that many threads can draw on concurrently, while another takes snapshots of it to
+/// draw to the terminal.
+///
+/// The cells of the canvas are split between a number of separately locked shards, so threads
+/// drawing to different parts of the canvas rarely wait for each other. All methods take
+/// `&self`, so a `SharedCanvas` can be shared between threads with an `Arc`.
+#[derive(Debug)]
+pub struct SharedCanvas {
+    shards: Vec<Mutex<Canvas>>,
+    width: u32,
+    height: u32,
+}
+
+impl SharedCanvas {
+    /// Creates a new `SharedCanvas` with the given width and height.
+    ///
+    /// As with `Canvas::new`, pixels can still be set outside the given dimensions.
+    pub fn new(width: u32, height: u32) -> SharedCanvas {
+        SharedCanvas {
+            shards: (0..SHARDS).map(|_| Mutex::new(Canvas::new(0, 0))).collect(),
+            width,
+            height,
+        }
+    }
+
+    /// Sets a pixel at the specified coordinates.
+    pub fn set(&self, x: u32, y: u32) {
+        self.shard(x, y).set(x, y);
+    }
+
+    /// Sets a pixel at the specified coordinates, specifying its colour.
+    pub fn set_colored(&self, x: u32, y: u32, color: PixelColor) {
+        self.shard(x, y).set_colored(x, y, color);
+    }
+
+    /// Deletes a pixel at the specified coordinates.
+    pub fn unset(&self, x: u32, y: u32) {
+        self.shard(x, y).unset(x, y);
+    }
+
+    /// Draws a line from `(x1, y1)` to `(x2, y2)`.
+    ///
+    /// The line is drawn one pixel at a time, so a snapshot taken concurrently may contain
+    /// only part of it.
+    pub fn line(&self, x1: u32, y1: u32, x2: u32, y2: u32) {
+        for (x, y) in line_points(x1, y1, x2, y2) {
+            self.set(x, y);
+        }
+    }
+
+    /// Draws a line from `(x1, y1)` to `(x2, y2)`, specifying its colour.
+    pub fn line_colored(&self, x1: u32, y1: u32, x2: u32, y2: u32, color: PixelColor) {
+        for (x, y) in line_points(x1, y1, x2, y2) {
+            self.set_colored(x, y, color);
+        }
+    }
+
+    /// Clears the canvas.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            lock(shard).clear();
+        }
+    }
+
+    /// Returns a copy of the current contents as an ordinary `Canvas`.
+    ///
+    /// Every shard is locked before any is copied, so the snapshot shows the canvas as it was
+    /// at a single moment: a pixel set after another never shows up without it.
+    pub fn snapshot(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        // The shards are always locked in the same order, and drawing only ever holds one lock
+        // at a time, so this can’t deadlock.
+        let shards: Vec<_> = self.shards.iter().map(lock).collect();
+        for shard in &shards {
+            canvas
+                .chars
+                .extend(shard.chars.iter().map(|(&pos, &cell)| (pos, cell)));
+        }
+        canvas
+    }
+
+    fn shard(&self, x: u32, y: u32) -> MutexGuard<'_, Canvas> {
+        let (col, row) = ((x / 2) as usize, (y / 4) as usize);
+        lock(&self.shards[(col / 4 + row * 7) % SHARDS])
+    }
+}
+
+fn lock(shard: &Mutex<Canvas>) -> MutexGuard<'_, Canvas> {
+    shard.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedCanvas;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn snapshots_are_consistent() {
+        // The pixels are set in order along a line crossing every shard many times, so any
+        // snapshot should hold some first part of the line and nothing after it.
+        const LENGTH: u32 = 4096;
+        let canvas = Arc::new(SharedCanvas::new(LENGTH, 4));
+        let writers: Vec<_> = (0..4)
+            .map(|y| {
+                let canvas = Arc::clone(&canvas);
+                thread::spawn(move || {
+                    for x in 0..LENGTH {
+                        canvas.set(x, y);
+                    }
+                })
+            })
+            .collect();
+        let mut done = false;
+        while !done {
+            done = writers.iter().all(|writer| writer.is_finished());
+            let snapshot = canvas.snapshot();
+            for y in 0..4 {
+                let drawn = (0..LENGTH).take_while(|&x| snapshot.get(x, y)).count() as u32;
+                assert!((drawn..LENGTH).all(|x| !snapshot.get(x, y)));
+                if done {
+                    assert_eq!(drawn, LENGTH);
+                }
+            }
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+    }
+}
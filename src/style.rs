@@ -0,0 +1,52 @@
+//! Styling of coloured characters on output.
+
+use std::fmt;
+
+use super::PixelColor;
+
+/// A way of giving characters their colour when a `Canvas` is drawn.
+///
+/// The default, `AnsiStyle`, uses the standard ANSI escape sequences. Implementing this trait
+/// allows other terminal libraries or custom escape sequences to be used instead.
+pub trait Style: fmt::Debug + Send + Sync {
+    /// Writes the character `c` to `out`, coloured with `color`.
+    fn paint(&self, out: &mut String, c: char, color: PixelColor);
+
+    /// Writes whatever makes the characters that follow coloured with `color`.
+    ///
+    /// This and `reset` are used instead of `paint` when a `Canvas` carries colours across
+    /// characters (see `ColorReset`). By default they write ANSI escape sequences.
+    fn set_color(&self, out: &mut String, color: PixelColor) {
+        out.push_str(&format!("\x1b[{}m", color.to_fg_str()));
+    }
+
+    /// Writes the character `c` to `out`, coloured with the colour at `index` in the
+    /// 256-colour palette, for terminals that can’t show true colours.
+    ///
+    /// By default this and `set_color_indexed` write ANSI escape sequences.
+    fn paint_indexed(&self, out: &mut String, c: char, index: u8) {
+        out.push_str(&format!("\x1b[38;5;{}m{}\x1b[0m", index, c));
+    }
+
+    /// Writes whatever makes the characters that follow coloured with the colour at `index` in
+    /// the 256-colour palette.
+    fn set_color_indexed(&self, out: &mut String, index: u8) {
+        out.push_str(&format!("\x1b[38;5;{}m", index));
+    }
+
+    /// Writes whatever returns the characters that follow to their normal colour.
+    fn reset(&self, out: &mut String) {
+        out.push_str("\x1b[0m");
+    }
+}
+
+/// The default `Style`, which colours each character with ANSI escape sequences and resets the
+/// colour after it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnsiStyle;
+
+impl Style for AnsiStyle {
+    fn paint(&self, out: &mut String, c: char, color: PixelColor) {
+        out.push_str(&format!("\x1b[{}m{}\x1b[0m", color.to_fg_str(), c));
+    }
+}
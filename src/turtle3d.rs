@@ -0,0 +1,373 @@
+//! Turtle graphics in three dimensions.
+//!
+//! A `Turtle3D` walks through space rather than across the canvas, and its path is projected
+//! onto the canvas through a `Camera`. It turns like the turtles of L-systems: left and right
+//! about its up direction, up and down about its left direction, and rolls about its heading.
+
+use std::cmp;
+
+use super::{degrees_to_radians, Canvas, PixelCanvas, PixelColor};
+
+/// A point or direction in space, as `[x, y, z]`.
+///
+/// The x axis points right, the y axis up and the z axis out of the screen, towards the
+/// camera when it hasn’t been turned.
+pub type Vec3 = [f32; 3];
+
+/// A camera projecting points in space onto a canvas, looking at its target from a distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// The point the camera looks at.
+    pub target: Vec3,
+    /// The pixel that the target is drawn at.
+    pub center: (f32, f32),
+    /// How far the camera has turned around the target, in degrees clockwise as seen from
+    /// above.
+    pub yaw: f32,
+    /// How far the camera has tilted to look down on the target, in degrees.
+    pub pitch: f32,
+    /// How far the camera is from the target. Perspective is stronger the closer it is, and an
+    /// infinite distance gives a flat, orthographic view.
+    pub distance: f32,
+    /// The number of pixels that a distance of `1.0` takes up at the target.
+    pub zoom: f32,
+}
+
+impl Camera {
+    /// Creates a `Camera` looking straight at the origin, which is drawn at the pixel
+    /// `(x, y)`, from a distance of `100.0` and with one pixel to each unit.
+    pub fn new(x: f32, y: f32) -> Camera {
+        Camera {
+            target: [0.0; 3],
+            center: (x, y),
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 100.0,
+            zoom: 1.0,
+        }
+    }
+
+    /// Returns the pixel that the point `p` is drawn at, or `None` if it is behind the camera.
+    pub fn project(&self, p: Vec3) -> Option<(f32, f32)> {
+        let p = sub(p, self.target);
+        let (sin, cos) = degrees_to_radians(self.yaw).sin_cos();
+        let (x, z) = (p[0] * cos - p[2] * sin, p[0] * sin + p[2] * cos);
+        let (sin, cos) = degrees_to_radians(self.pitch).sin_cos();
+        let (y, z) = (p[1] * cos - z * sin, p[1] * sin + z * cos);
+        let scale = if self.distance.is_finite() {
+            let depth = self.distance - z;
+            if depth <= 1e-3 {
+                return None;
+            }
+            self.zoom * self.distance / depth
+        } else {
+            self.zoom
+        };
+        Some((self.center.0 + x * scale, self.center.1 - y * scale))
+    }
+}
+
+/// A ‘turtle’ that walks through space drawing lines, seen through a `Camera`.
+///
+/// The turtle’s orientation is kept as three directions at right angles to each other: its
+/// heading, its left and its up. It starts at the origin heading up the y axis, with its left
+/// along the negative x axis, which suits drawing plants. Angles are in degrees.
+pub struct Turtle3D<C = Canvas> {
+    pub position: Vec3,
+    pub heading: Vec3,
+    pub left: Vec3,
+    pub up: Vec3,
+    pub brush: bool,
+    pub use_color: bool,
+    pub brush_color: PixelColor,
+    pub camera: Camera,
+    pub cvs: C,
+    stack: Vec<(Vec3, Vec3, Vec3, Vec3)>,
+}
+
+impl Turtle3D {
+    /// Creates a new `Turtle3D` drawing onto a new `Canvas` through the given camera.
+    ///
+    /// The turtle starts with its brush down.
+    pub fn new(camera: Camera) -> Turtle3D {
+        Turtle3D::with_canvas(camera, Canvas::new(0, 0))
+    }
+}
+
+impl<C: PixelCanvas> Turtle3D<C> {
+    /// Creates a new `Turtle3D` that draws onto the given canvas through the given camera.
+    ///
+    /// The turtle starts with its brush down.
+    pub fn with_canvas(camera: Camera, cvs: C) -> Turtle3D<C> {
+        Turtle3D {
+            position: [0.0; 3],
+            heading: [0.0, 1.0, 0.0],
+            left: [-1.0, 0.0, 0.0],
+            up: [0.0, 0.0, 1.0],
+            brush: true,
+            use_color: false,
+            brush_color: PixelColor::White,
+            camera,
+            cvs,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Lifts the `Turtle3D`’s brush.
+    pub fn pen_up(&mut self) {
+        self.brush = false;
+    }
+
+    /// Puts down the `Turtle3D`’s brush.
+    pub fn pen_down(&mut self) {
+        self.brush = true;
+    }
+
+    /// Uses the given colour for the brush.
+    pub fn color(&mut self, brush_color: PixelColor) {
+        self.use_color = true;
+        self.brush_color = brush_color;
+    }
+
+    /// Removes the colour from the brush.
+    pub fn clean_brush(&mut self) {
+        self.use_color = false;
+    }
+
+    /// Moves the `Turtle3D` forward by `dist` steps along its heading, drawing a line if its
+    /// brush is down.
+    pub fn forward(&mut self, dist: f32) {
+        let to = add(self.position, scale(self.heading, dist));
+        self.teleport(to);
+    }
+
+    /// Moves the `Turtle3D` backward by `dist` steps.
+    pub fn back(&mut self, dist: f32) {
+        self.forward(-dist);
+    }
+
+    /// Teleports the `Turtle3D` to the given point, drawing a line if its brush is down.
+    ///
+    /// Lines with an end behind the camera aren’t drawn. Vertical distances on the canvas are
+    /// scaled by its pixel aspect about the camera’s centre, as the steps of a `Turtle` are, so
+    /// shapes keep their proportions on canvases whose pixels aren’t square.
+    pub fn teleport(&mut self, to: Vec3) {
+        let from = self.position;
+        self.position = to;
+        if !self.brush {
+            return;
+        }
+        let (a, b) = match (self.camera.project(from), self.camera.project(to)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+        let (center, aspect) = (self.camera.center.1, self.cvs.pixel_aspect());
+        let vertical = |y: f32| center + (y - center) * aspect;
+        let pixel = |v: f32| cmp::max(0, v.round() as i32) as u32;
+        let (x1, x2) = (pixel(a.0), pixel(b.0));
+        let (y1, y2) = (pixel(vertical(a.1)), pixel(vertical(b.1)));
+        if self.use_color {
+            self.cvs.line_colored(x1, y1, x2, y2, self.brush_color);
+        } else {
+            self.cvs.line(x1, y1, x2, y2);
+        }
+    }
+
+    /// Turns the `Turtle3D` left by `angle` degrees, about its up direction.
+    pub fn left(&mut self, angle: f32) {
+        let (h, l) = rotate(self.heading, self.left, angle);
+        self.heading = h;
+        self.left = l;
+    }
+
+    /// Turns the `Turtle3D` right by `angle` degrees, about its up direction.
+    pub fn right(&mut self, angle: f32) {
+        self.left(-angle);
+    }
+
+    /// Tilts the `Turtle3D`’s heading up by `angle` degrees, about its left direction.
+    pub fn pitch_up(&mut self, angle: f32) {
+        let (h, u) = rotate(self.heading, self.up, angle);
+        self.heading = h;
+        self.up = u;
+    }
+
+    /// Tilts the `Turtle3D`’s heading down by `angle` degrees.
+    pub fn pitch_down(&mut self, angle: f32) {
+        self.pitch_up(-angle);
+    }
+
+    /// Rolls the `Turtle3D` clockwise (as seen from behind) by `angle` degrees, about its
+    /// heading.
+    pub fn roll(&mut self, angle: f32) {
+        let (l, u) = rotate(self.left, self.up, angle);
+        self.left = l;
+        self.up = u;
+    }
+
+    /// Saves the position and orientation of the `Turtle3D`, to go back to with `pop`.
+    ///
+    /// Saved states are kept on a stack, for drawing the branches of L-systems.
+    pub fn push(&mut self) {
+        self.stack
+            .push((self.position, self.heading, self.left, self.up));
+    }
+
+    /// Moves the `Turtle3D` back to the position and orientation last saved with `push`,
+    /// without drawing anything. Returns whether there was one.
+    pub fn pop(&mut self) -> bool {
+        match self.stack.pop() {
+            Some((position, heading, left, up)) => {
+                self.position = position;
+                self.heading = heading;
+                self.left = left;
+                self.up = up;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Writes the `Turtle3D`’s canvas to a `String` and returns it.
+    pub fn frame(&self) -> String {
+        self.cvs.frame()
+    }
+}
+
+/// Rotates `a` towards `b` by `angle` degrees in the plane they share, returning both.
+fn rotate(a: Vec3, b: Vec3, angle: f32) -> (Vec3, Vec3) {
+    let (sin, cos) = degrees_to_radians(angle).sin_cos();
+    (
+        add(scale(a, cos), scale(b, sin)),
+        sub(scale(b, cos), scale(a, sin)),
+    )
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: Vec3, s: f32) -> Vec3 {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Camera, Turtle3D, Vec3};
+    use Canvas;
+
+    fn near(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4
+    }
+
+    fn near3(a: Vec3, b: Vec3) -> bool {
+        (0..3).all(|i| (a[i] - b[i]).abs() < 1e-4)
+    }
+
+    fn flat(x: f32, y: f32) -> Camera {
+        Camera {
+            distance: f32::INFINITY,
+            ..Camera::new(x, y)
+        }
+    }
+
+    #[test]
+    fn orthographic_projection() {
+        let camera = flat(20.0, 20.0);
+        assert!(near(camera.project([0.0; 3]).unwrap(), (20.0, 20.0)));
+        // Up the y axis is up the canvas, and depth makes no difference.
+        assert!(near(camera.project([3.0, 4.0, 0.0]).unwrap(), (23.0, 16.0)));
+        assert!(near(
+            camera.project([3.0, 4.0, -50.0]).unwrap(),
+            (23.0, 16.0)
+        ));
+
+        let zoomed = Camera {
+            zoom: 2.0,
+            target: [1.0, 1.0, 0.0],
+            ..camera
+        };
+        assert!(near(zoomed.project([1.0, 1.0, 0.0]).unwrap(), (20.0, 20.0)));
+        assert!(near(zoomed.project([3.0, 1.0, 0.0]).unwrap(), (24.0, 20.0)));
+    }
+
+    #[test]
+    fn perspective_projection() {
+        let camera = Camera::new(20.0, 20.0);
+        // Halfway to the camera, things look twice as big; twice as far, half as big.
+        assert!(near(
+            camera.project([10.0, 0.0, 50.0]).unwrap(),
+            (40.0, 20.0)
+        ));
+        assert!(near(
+            camera.project([10.0, 10.0, -100.0]).unwrap(),
+            (25.0, 15.0)
+        ));
+        assert_eq!(camera.project([0.0, 0.0, 100.0]), None);
+        assert_eq!(camera.project([0.0, 0.0, 150.0]), None);
+    }
+
+    #[test]
+    fn turned_cameras() {
+        // Turned a quarter of the way round, the camera looks along the x axis and sees the z
+        // axis pointing left.
+        let camera = Camera {
+            yaw: 90.0,
+            ..flat(20.0, 20.0)
+        };
+        assert!(near(camera.project([1.0, 0.0, 0.0]).unwrap(), (20.0, 20.0)));
+        assert!(near(camera.project([0.0, 0.0, 1.0]).unwrap(), (19.0, 20.0)));
+        // Looking straight down, the z axis points down the canvas.
+        let camera = Camera {
+            pitch: 90.0,
+            ..flat(20.0, 20.0)
+        };
+        assert!(near(camera.project([0.0, 1.0, 0.0]).unwrap(), (20.0, 20.0)));
+        assert!(near(camera.project([0.0, 0.0, 1.0]).unwrap(), (20.0, 21.0)));
+    }
+
+    #[test]
+    fn turtle_walks_and_turns() {
+        let mut turtle = Turtle3D::new(flat(20.0, 20.0));
+        turtle.forward(10.0);
+        assert!(near3(turtle.position, [0.0, 10.0, 0.0]));
+        assert!((10..=20).all(|y| turtle.cvs.get(20, y)));
+
+        turtle.push();
+        turtle.left(90.0);
+        turtle.forward(5.0);
+        assert!(near3(turtle.position, [-5.0, 10.0, 0.0]));
+        assert!((15..=20).all(|x| turtle.cvs.get(x, 10)));
+        turtle.pitch_up(90.0);
+        assert!(near3(turtle.heading, [0.0, 0.0, 1.0]));
+        turtle.roll(90.0);
+        assert!(near3(turtle.left, [1.0, 0.0, 0.0]));
+
+        assert!(turtle.pop());
+        assert!(near3(turtle.position, [0.0, 10.0, 0.0]));
+        assert!(near3(turtle.heading, [0.0, 1.0, 0.0]));
+        assert!(!turtle.pop());
+        turtle.pen_up();
+        turtle.forward(5.0);
+        assert!(!turtle.cvs.get(20, 5));
+    }
+
+    #[test]
+    fn turtle_follows_pixel_aspect() {
+        // With pixels twice as wide as they are tall, vertical steps take twice as many
+        // pixels, measured from the camera’s centre.
+        let mut canvas = Canvas::new(0, 0);
+        canvas.set_pixel_aspect(2.0);
+        let mut turtle = Turtle3D::with_canvas(flat(20.0, 30.0), canvas);
+        turtle.forward(10.0);
+        turtle.left(90.0);
+        turtle.forward(5.0);
+        assert!((10..=30).all(|y| turtle.cvs.get(20, y)));
+        assert!(!turtle.cvs.get(20, 9));
+        assert!((15..=20).all(|x| turtle.cvs.get(x, 10)));
+    }
+}